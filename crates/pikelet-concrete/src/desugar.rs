@@ -2,12 +2,13 @@ use codespan::{ByteIndex, ByteOffset, ByteSpan};
 use codespan_reporting::{Diagnostic, Label as DiagnosticLabel};
 use im;
 use failure::Fail;
-use moniker::{Binder, Embed, FreeVar, Nest, Scope, Var};
+use moniker::{Binder, BoundTerm, Embed, FreeVar, Nest, Scope, Var};
 
 use pikelet_core::syntax::{Label, Level, LevelShift};
 
 use crate::syntax::concrete;
 use crate::syntax::raw;
+use crate::syntax::Unfold;
 
 /// The environment used when desugaring from the concrete to raw syntax
 #[derive(Debug, Clone)]
@@ -27,11 +28,12 @@ impl DesugarEnv {
         DesugarEnv { locals: mappings }
     }
 
-    pub fn on_item(&mut self, name: &str) -> Binder<String> {
-        if let Some(free_var) = self.locals.get(name) {
-            return Binder(free_var.clone());
-        }
-        Binder(self.on_binding(name))
+    /// The names that are currently in scope, along with the free variables
+    /// that they refer to
+    pub fn locals(&self) -> impl Iterator<Item = (&str, &FreeVar<String>)> {
+        self.locals
+            .iter()
+            .map(|(name, free_var)| (name.as_str(), free_var))
     }
 
     pub fn on_binding(&mut self, name: &str) -> FreeVar<String> {
@@ -78,6 +80,13 @@ pub enum DesugarError {
         duplicate_span: ByteSpan,
         name: String,
     },
+    #[fail(display = "Definition depends on itself: `{}`", name)]
+    RecursiveDefinitions {
+        span: ByteSpan,
+        name: String,
+        /// The other definitions that make up the cycle
+        via: Vec<(ByteSpan, String)>,
+    },
 }
 
 impl DesugarError {
@@ -128,6 +137,22 @@ impl DesugarError {
                 DiagnosticLabel::new_secondary(original_span)
                     .with_message("the original definition"),
             ),
+            DesugarError::RecursiveDefinitions {
+                span,
+                ref name,
+                ref via,
+            } => {
+                let diagnostic = Diagnostic::new_error(format!(
+                    "recursive definitions are not yet supported, but `{}` depends on itself",
+                    name,
+                ))
+                .with_label(DiagnosticLabel::new_primary(span).with_message("the definition"));
+
+                via.iter().fold(diagnostic, |diagnostic, &(span, _)| {
+                    let label = DiagnosticLabel::new_secondary(span);
+                    diagnostic.with_label(label.with_message("through this definition"))
+                })
+            },
         }
     }
 }
@@ -186,11 +211,15 @@ fn desugar_fun_ty(
 /// ```text
 /// \(a : t1) => \(b : t1) => \c => \(d : t2) => t3
 /// ```
+///
+/// Any `where_items` are desugared into a let binding around the body, so that
+/// they can refer to the parameters without escaping into the outer scope.
 fn desugar_fun_intro(
     env: &DesugarEnv,
     param_groups: &[concrete::FunIntroParamGroup],
     return_ann: Option<&concrete::Term>,
     body: &concrete::Term,
+    where_items: &[concrete::Item],
 ) -> Result<raw::RcTerm, DesugarError> {
     let mut env = env.clone();
 
@@ -207,9 +236,19 @@ fn desugar_fun_intro(
         }));
     }
 
+    let body = match where_items {
+        [] => body.desugar(&env)?,
+        _ => {
+            let mut body_env = env.clone();
+            let items = desugar_items(&mut body_env, where_items)?;
+
+            wrap_items(body.span(), items, body.desugar(&body_env)?)
+        },
+    };
+
     let body = match return_ann {
-        None => body.desugar(&env)?,
-        Some(ann) => raw::RcTerm::from(raw::Term::Ann(body.desugar(&env)?, ann.desugar(&env)?)),
+        None => body,
+        Some(ann) => raw::RcTerm::from(raw::Term::Ann(body, ann.desugar(&env)?)),
     };
 
     Ok(params
@@ -223,10 +262,195 @@ fn desugar_fun_intro(
         }))
 }
 
+/// Determine how the item with the given name should be unfolded, using the
+/// attributes on both its declaration and its definition
+///
+/// If an item is marked as both `inline` and `no_unfold`, `no_unfold` wins.
+fn item_unfold(concrete_items: &[concrete::Item], name: &str) -> Unfold {
+    (concrete_items.iter())
+        .filter(|item| item.name() == Some(name))
+        .flat_map(concrete::Item::attrs)
+        .map(|attr| match attr.name.1.as_str() {
+            "inline" => Unfold::Inline,
+            "no_unfold" => Unfold::Never,
+            _ => Unfold::Default,
+        })
+        .max()
+        .unwrap_or(Unfold::Default)
+}
+
+/// An item that has been desugared, but not yet sorted
+enum RawItem {
+    /// A definition, along with how it should be unfolded
+    Binding(Binder<String>, Unfold, Embed<raw::RcTerm>),
+    /// A record whose fields should be brought into scope
+    Open(raw::RcTerm, Option<Vec<(ByteSpan, Label)>>),
+}
+
+impl RawItem {
+    fn term(&self) -> &raw::RcTerm {
+        match *self {
+            RawItem::Binding(_, _, Embed(ref term)) | RawItem::Open(ref term, _) => term,
+        }
+    }
+
+    fn binder(&self) -> Option<&FreeVar<String>> {
+        match *self {
+            RawItem::Binding(Binder(ref free_var), _, _) => Some(free_var),
+            RawItem::Open(_, _) => None,
+        }
+    }
+}
+
+/// Sort the items so that they come after the items they depend on, keeping
+/// them in their original order where possible
+///
+/// Items must not depend on themselves, either directly or through other
+/// items, because we have no way of elaborating recursive definitions yet.
+/// References from an item to its own name are not counted as dependencies,
+/// because they refer to the binding it shadows, if there is one.
+///
+/// The items that follow an open are assumed to depend on it, unless the
+/// open itself depends on them.
+fn sort_items(items: Vec<(ByteSpan, RawItem)>) -> Result<Vec<RawItem>, DesugarError> {
+    #[derive(Copy, Clone, PartialEq)]
+    enum Mark {
+        Unvisited,
+        Visiting,
+        Visited,
+    }
+
+    fn visit(
+        index: usize,
+        dependencies: &[Vec<usize>],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), Vec<usize>> {
+        match marks[index] {
+            Mark::Visited => return Ok(()),
+            Mark::Visiting => {
+                let start = path.iter().position(|&i| i == index).unwrap();
+                return Err(path[start..].to_vec());
+            },
+            Mark::Unvisited => {},
+        }
+
+        marks[index] = Mark::Visiting;
+        path.push(index);
+        for &dependency in &dependencies[index] {
+            visit(dependency, dependencies, marks, path, order)?;
+        }
+        path.pop();
+        marks[index] = Mark::Visited;
+        order.push(index);
+
+        Ok(())
+    }
+
+    fn reaches(index: usize, target: usize, dependencies: &[Vec<usize>]) -> bool {
+        let mut seen = vec![false; dependencies.len()];
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            if index == target {
+                return true;
+            }
+            if !seen[index] {
+                seen[index] = true;
+                stack.extend(&dependencies[index]);
+            }
+        }
+        false
+    }
+
+    let mut dependencies = (items.iter().enumerate())
+        .map(|(index, &(_, ref item))| {
+            let free_vars = item.term().free_vars();
+            (items.iter().enumerate())
+                .filter(|&(other, &(_, ref other_item))| match other_item.binder() {
+                    Some(free_var) => other != index && free_vars.contains(free_var),
+                    None => false,
+                })
+                .map(|(other, _)| other)
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+
+    // Adding these dependencies can never introduce a cycle, because we
+    // check that the open does not already depend on the later item
+    for open in 0..items.len() {
+        if let (_, RawItem::Open(_, _)) = items[open] {
+            for later in open + 1..items.len() {
+                if !reaches(open, later, &dependencies) {
+                    dependencies[later].push(open);
+                }
+            }
+        }
+    }
+
+    let mut marks = vec![Mark::Unvisited; items.len()];
+    let mut order = Vec::with_capacity(items.len());
+    for index in 0..items.len() {
+        let mut path = Vec::new();
+        if let Err(cycle) = visit(index, &dependencies, &mut marks, &mut path, &mut order) {
+            let name = |i: usize| {
+                let free_var = items[i].1.binder();
+                free_var.and_then(|free_var| free_var.pretty_name.clone())
+            };
+            let via = (cycle[1..].iter())
+                .map(|&i| (items[i].0, name(i).unwrap_or_default()))
+                .collect();
+
+            return Err(DesugarError::RecursiveDefinitions {
+                span: items[cycle[0]].0,
+                name: name(cycle[0]).unwrap_or_default(),
+                via,
+            });
+        }
+    }
+
+    let mut items = (items.into_iter())
+        .map(|(_, item)| Some(item))
+        .collect::<Vec<_>>();
+
+    Ok(order.into_iter().filter_map(|i| items[i].take()).collect())
+}
+
+/// Wrap the body in the sorted items, starting a new let binding after each
+/// open
+fn wrap_items(span: ByteSpan, items: Vec<RawItem>, body: raw::RcTerm) -> raw::RcTerm {
+    fn desugar_bindings(
+        span: ByteSpan,
+        bindings: &mut Vec<(Binder<String>, Unfold, Embed<raw::RcTerm>)>,
+        body: raw::RcTerm,
+    ) -> raw::RcTerm {
+        if bindings.is_empty() {
+            return body;
+        }
+
+        let nest = Nest::new(bindings.drain(..).rev().collect());
+        raw::RcTerm::from(raw::Term::Let(span, Scope::new(nest, body)))
+    }
+
+    let mut bindings = Vec::new();
+    let body = items.into_iter().rev().fold(body, |body, item| match item {
+        RawItem::Binding(binder, unfold, term) => {
+            bindings.push((binder, unfold, term));
+            body
+        },
+        RawItem::Open(module, using) => {
+            let body = desugar_bindings(span, &mut bindings, body);
+            raw::RcTerm::from(raw::Term::Open(span, module, using, body))
+        },
+    });
+
+    desugar_bindings(span, &mut bindings, body)
+}
+
 fn desugar_items(
     env: &mut DesugarEnv,
     concrete_items: &[concrete::Item],
-) -> Result<Nest<(Binder<String>, Embed<raw::RcTerm>)>, DesugarError> {
+) -> Result<Vec<RawItem>, DesugarError> {
     use im::HashMap;
 
     #[derive(Clone)]
@@ -241,18 +465,36 @@ fn desugar_items(
     let mut items = Vec::with_capacity(concrete_items.len());
     let hole = raw::RcTerm::from(raw::Term::Hole(ByteSpan::default()));
 
+    // Bind the names up-front, so that items can refer to the items that are
+    // defined after them. Names that shadow an existing binding are only
+    // bound once we reach their definition, so that the items before it still
+    // refer to the binding that they shadow.
+    let mut binders = HashMap::new();
+    let mut shadowing = Vec::new();
+    for name in concrete_items.iter().filter_map(concrete::Item::name) {
+        if binders.contains_key(name) || shadowing.contains(&name) {
+            continue;
+        }
+        match env.locals.get(name) {
+            Some(_) => shadowing.push(name),
+            None => {
+                binders.insert(name, Binder(env.on_binding(name)));
+            },
+        }
+    }
+
     // Iterate through the items in the module, checking each in turn
     for concrete_item in concrete_items {
         match *concrete_item {
             concrete::Item::Declaration {
                 name: (start, ref name),
                 ref ann,
+                ..
             } => {
-                let binder = env.on_item(name);
                 let name_span = ByteSpan::from_offset(start, ByteOffset::from_str(name));
 
                 // Ensure that this declaration has not already been seen
-                match forward_declarations.get(&binder) {
+                match forward_declarations.get(name) {
                     // There's already a definition associated with this name -
                     // we can't add a new declaration for it!
                     Some(&ForwardDecl::Defined(definition_span)) => {
@@ -279,7 +521,7 @@ fn desugar_items(
 
                 // Remember the declaration for when we get to a subsequent definition
                 let declaration = ForwardDecl::Pending(name_span, ann.desugar(&env)?);
-                forward_declarations.insert(binder.clone(), declaration);
+                forward_declarations.insert(name.clone(), declaration);
             },
 
             concrete::Item::Definition {
@@ -287,12 +529,13 @@ fn desugar_items(
                 ref params,
                 ref return_ann,
                 ref body,
+                ref where_items,
+                ..
             } => {
-                let binder = env.on_item(name);
                 let name_span = ByteSpan::from_offset(start, ByteOffset::from_str(name));
-                let term =
-                    desugar_fun_intro(env, params, return_ann.as_ref().map(<_>::as_ref), body)?;
-                let ann = match forward_declarations.get(&binder).cloned() {
+                let return_ann = return_ann.as_ref().map(<_>::as_ref);
+                let term = desugar_fun_intro(env, params, return_ann, body, where_items)?;
+                let ann = match forward_declarations.get(name).cloned() {
                     // This declaration was already given a definition, so this
                     // is an error!
                     //
@@ -316,15 +559,37 @@ fn desugar_items(
                 // We must not remove this from the list of pending
                 // declarations, lest we encounter another declaration or
                 // definition of the same name later on!
-                forward_declarations.insert(binder.clone(), ForwardDecl::Defined(name_span));
+                forward_declarations.insert(name.clone(), ForwardDecl::Defined(name_span));
+                let binder = match binders.get(name.as_str()) {
+                    Some(binder) => binder.clone(),
+                    None => Binder(env.on_binding(name)),
+                };
                 // Add the definition to the elaborated items
-                items.push((binder, Embed(raw::RcTerm::from(raw::Term::Ann(term, ann)))));
+                let unfold = item_unfold(concrete_items, name);
+                let term = raw::RcTerm::from(raw::Term::Ann(term, ann));
+                items.push((name_span, RawItem::Binding(binder, unfold, Embed(term))));
+            },
+            concrete::Item::Open {
+                span,
+                ref module,
+                ref using,
+            } => {
+                let using = using.as_ref().map(|using| {
+                    (using.iter())
+                        .map(|&(start, ref name)| {
+                            let span = ByteSpan::from_offset(start, ByteOffset::from_str(name));
+                            (span, Label(name.clone()))
+                        })
+                        .collect()
+                });
+
+                items.push((span, RawItem::Open(module.desugar(env)?, using)));
             },
             concrete::Item::Error(_) => unimplemented!("error recovery"),
         }
     }
 
-    Ok(Nest::new(items))
+    sort_items(items)
 }
 
 fn desugar_let(
@@ -335,11 +600,9 @@ fn desugar_let(
 ) -> Result<raw::RcTerm, DesugarError> {
     let mut env = env.clone();
     let items = desugar_items(&mut env, concrete_items)?;
+    let span = ByteSpan::new(start, body.span().end());
 
-    Ok(raw::RcTerm::from(raw::Term::Let(
-        ByteSpan::new(start, body.span().end()),
-        Scope::new(items, body.desugar(&env)?),
-    )))
+    Ok(wrap_items(span, items, body.desugar(&env)?))
 }
 
 fn desugar_where(
@@ -351,11 +614,10 @@ fn desugar_where(
     let mut env = env.clone();
     let items = desugar_items(&mut env, concrete_items)?;
 
+    let span = ByteSpan::new(body.span().start(), end);
+
     // TODO: Remember formatting
-    Ok(raw::RcTerm::from(raw::Term::Let(
-        ByteSpan::new(body.span().start(), end),
-        Scope::new(items, body.desugar(&env)?),
-    )))
+    Ok(wrap_items(span, items, body.desugar(&env)?))
 }
 
 fn desugar_record_ty(
@@ -409,7 +671,7 @@ fn desugar_record_intro(
                 ref term,
             } => Ok((
                 Label(name.clone()),
-                desugar_fun_intro(env, params, return_ann.as_ref().map(<_>::as_ref), term)?,
+                desugar_fun_intro(env, params, return_ann.as_ref().map(<_>::as_ref), term, &[])?,
             )),
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -506,7 +768,7 @@ impl Desugar<raw::RcTerm> for concrete::Term {
             )),
             concrete::Term::FunType(_, ref params, ref body) => desugar_fun_ty(env, params, body),
             concrete::Term::FunIntro(_, ref params, ref body) => {
-                desugar_fun_intro(env, params, None, body)
+                desugar_fun_intro(env, params, None, body, &[])
             },
             concrete::Term::FunArrow(ref ann, ref body) => {
                 Ok(raw::RcTerm::from(raw::Term::FunType(
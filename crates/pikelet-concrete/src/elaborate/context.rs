@@ -1,14 +1,20 @@
+use codespan::ByteSpan;
 use im;
 use moniker::{Binder, FreeVar, Var};
+use std::cell::RefCell;
+use std::mem;
 use std::rc::Rc;
 
+use pikelet_core::limits::Limits;
 use pikelet_core::nbe;
-use pikelet_core::syntax::core::RcTerm;
+use pikelet_core::syntax::core::{RcTerm, Term};
 use pikelet_core::syntax::domain::{RcType, RcValue, Value};
-use pikelet_core::syntax::{Import, Literal};
+use pikelet_core::syntax::{Import, Label, Literal};
 
 use crate::resugar::{Resugar, ResugarEnv};
 
+use super::trace::Trace;
+
 // Some helper traits for marshalling between Rust and Pikelet values
 //
 // I'm not super happy with the API at the moment, so these are currently private
@@ -183,6 +189,52 @@ pub struct Globals {
     ty_f32: RcType,
     ty_f64: RcType,
     var_array: FreeVar<String>,
+    var_io: FreeVar<String>,
+}
+
+/// A tally of the entries stored in a context, for tracking down leaks
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub imports: usize,
+    pub declarations: usize,
+    pub definitions: usize,
+    /// The number of definitions that have had their normal forms cached
+    pub cached_nfs: usize,
+    /// The number of nodes in the core terms of the definitions and imports
+    pub term_nodes: usize,
+}
+
+impl MemoryUsage {
+    /// An estimate of the number of bytes allocated for the core terms,
+    /// ignoring any sharing between them
+    pub fn term_bytes(&self) -> usize {
+        // Each node is stored in an `Rc`, alongside its reference counts
+        self.term_nodes * (mem::size_of::<Term>() + 2 * mem::size_of::<usize>())
+    }
+}
+
+/// A definition, along with its normal form once it has been computed
+///
+/// The normal form is shared between the copies of the context that are made
+/// when entering scopes, so that it only needs to be computed once.
+#[derive(Clone, Debug)]
+struct Definition {
+    term: RcTerm,
+    nf: Rc<RefCell<Option<RcValue>>>,
+}
+
+/// A record whose fields have been brought into scope with `open`
+#[derive(Clone, Debug)]
+pub struct Open {
+    /// The span of the record that was opened
+    pub span: ByteSpan,
+    /// The variable that the record is bound to, which the fields are
+    /// projected from
+    pub var: FreeVar<String>,
+    pub term: RcTerm,
+    pub ty: RcType,
+    /// The labels of the fields that were brought into scope
+    pub labels: Vec<Label>,
 }
 
 /// The type checking context
@@ -207,14 +259,23 @@ pub struct Context {
     /// The type annotations of the binders we have passed over
     declarations: im::HashMap<FreeVar<String>, RcType>,
     /// Any definitions we have passed over
-    definitions: im::HashMap<FreeVar<String>, RcTerm>,
+    definitions: im::HashMap<FreeVar<String>, Definition>,
+    /// The number of declarations that belong to the built-in prelude
+    prelude_declarations: usize,
+    /// The records that have been opened, keyed by the labels of the fields
+    /// they bring into scope
+    ///
+    /// These are used to look up the names that are not otherwise in scope.
+    opens: im::HashMap<String, Vec<Rc<Open>>>,
+    /// The limits to enforce when normalizing terms
+    limits: Limits,
+    /// Where to record the elaboration steps, if tracing is enabled
+    trace: Option<Rc<Trace>>,
 }
 
 impl Default for Context {
     fn default() -> Context {
-        use moniker::{Embed, Scope};
-
-        use pikelet_core::syntax::core::Term;
+        use moniker::{Embed, Nest, Scope};
 
         let var_bool = FreeVar::fresh_named("Bool");
         let var_true = FreeVar::fresh_named("true");
@@ -232,6 +293,7 @@ impl Default for Context {
         let var_f32 = FreeVar::fresh_named("F32");
         let var_f64 = FreeVar::fresh_named("F64");
         let var_array = FreeVar::fresh_named("Array");
+        let var_io = FreeVar::fresh_named("IO");
 
         let mut context = Context {
             resugar_env: ResugarEnv::new(),
@@ -250,10 +312,15 @@ impl Default for Context {
                 ty_f32: RcValue::from(Value::var(Var::Free(var_f32.clone()), 0)),
                 ty_f64: RcValue::from(Value::var(Var::Free(var_f64.clone()), 0)),
                 var_array: var_array.clone(),
+                var_io: var_io.clone(),
             }),
             imports: im::HashMap::new(),
             declarations: im::HashMap::new(),
             definitions: im::HashMap::new(),
+            prelude_declarations: 0,
+            opens: im::HashMap::new(),
+            limits: Limits::default(),
+            trace: None,
         };
 
         let universe0 = RcValue::from(Value::universe(0));
@@ -284,6 +351,13 @@ impl Default for Context {
         context.insert_declaration(var_f32, universe0.clone());
         context.insert_declaration(var_f64, universe0.clone());
         context.insert_declaration(var_array, array_ty);
+        context.insert_declaration(
+            var_io.clone(),
+            RcValue::from(Value::FunType(Scope::new(
+                (Binder(FreeVar::fresh_unnamed()), Embed(universe0.clone())),
+                universe0.clone(),
+            ))),
+        );
 
         context.insert_declaration(var_true.clone(), bool_ty.clone());
         context.insert_declaration(var_false.clone(), bool_ty.clone());
@@ -443,6 +517,10 @@ impl Default for Context {
         prim_import!("prim/f64/div", fn(x: f64, y: f64) -> f64 { x / y });
 
         prim_import!("prim/char/to-string", fn(val: char) -> String { val.to_string() });
+        prim_import!("prim/char/to-u32", fn(val: char) -> u32 { *val as u32 });
+        prim_import!("prim/u32/to-char", fn(val: u32) -> char {
+            std::char::from_u32(*val).unwrap_or(std::char::REPLACEMENT_CHARACTER)
+        });
         prim_import!("prim/u8/to-string", fn(val: u8) -> String { val.to_string() });
         prim_import!("prim/u16/to-string", fn(val: u16) -> String { val.to_string() });
         prim_import!("prim/u32/to-string", fn(val: u32) -> String { val.to_string() });
@@ -456,6 +534,58 @@ impl Default for Context {
 
         prim_import!("prim/string/append", fn(x: String, y: String) -> String { x.clone() + y }); // FIXME: Clone
 
+        // The `IO` primitives are left uninterpreted during normalization, and
+        // are instead carried out by `pikelet_core::run::run_io`
+        {
+            use pikelet_core::run;
+            use pikelet_core::syntax::domain::{Neutral, RcNeutral};
+
+            fn interpretation(_: &[RcValue]) -> Option<RcValue> {
+                None
+            }
+
+            let io = |ty: RcType| {
+                let head = RcNeutral::from(Neutral::var(Var::Free(var_io.clone()), 0));
+                RcValue::from(Value::Neutral(head, vec![ty]))
+            };
+            let arrow = |param_ty: RcType, body_ty: RcType| {
+                let param = (Binder(FreeVar::fresh_unnamed()), Embed(param_ty));
+                RcValue::from(Value::FunType(Scope::new(param, body_ty)))
+            };
+            let forall = |param_var: &FreeVar<String>, body_ty: RcType| {
+                let param = (Binder(param_var.clone()), Embed(universe0.clone()));
+                RcValue::from(Value::FunType(Scope::new(param, body_ty)))
+            };
+
+            let a = FreeVar::fresh_named("a");
+            let b = FreeVar::fresh_named("b");
+            let ty_a = RcValue::from(Value::var(Var::Free(a.clone()), 0));
+            let ty_b = RcValue::from(Value::var(Var::Free(b.clone()), 0));
+            let ty_unit = RcValue::from(Value::RecordType(Scope::new(Nest::new(vec![]), ())));
+
+            let print_ty = arrow(context.string().clone(), io(ty_unit));
+            let pure_ty = forall(&a, arrow(ty_a.clone(), io(ty_a.clone())));
+            let bind_ty = forall(
+                &a,
+                forall(
+                    &b,
+                    arrow(
+                        io(ty_a.clone()),
+                        arrow(arrow(ty_a, io(ty_b.clone())), io(ty_b)),
+                    ),
+                ),
+            );
+
+            for &(name, ref ty) in &[
+                (run::PRINT, print_ty),
+                (run::PURE, pure_ty),
+                (run::BIND, bind_ty),
+            ] {
+                context.insert_import(name.to_owned(), Import::Prim(interpretation), ty.clone());
+            }
+        }
+
+        context.prelude_declarations = context.declarations.len();
         context
     }
 }
@@ -465,6 +595,31 @@ impl Context {
         src.resugar(&self.resugar_env)
     }
 
+    /// Tally up the entries stored in the context
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let import_terms = self
+            .imports
+            .values()
+            .filter_map(|&(ref import, _)| match *import {
+                Import::Term(ref term) => Some(term),
+                Import::Opaque | Import::Prim(_) => None,
+            });
+        let definition_terms = self.definitions.values().map(|definition| &definition.term);
+
+        MemoryUsage {
+            imports: self.imports.len(),
+            declarations: self.declarations.len(),
+            definitions: self.definitions.len(),
+            cached_nfs: (self.definitions.values())
+                .filter(|definition| definition.nf.borrow().is_some())
+                .count(),
+            term_nodes: import_terms
+                .chain(definition_terms)
+                .map(RcTerm::node_count)
+                .sum(),
+        }
+    }
+
     pub fn mappings(&self) -> im::HashMap<String, FreeVar<String>> {
         self.declarations
             .iter()
@@ -543,30 +698,79 @@ impl Context {
         }
     }
 
+    pub fn io<'a>(&self, ty: &'a RcType) -> Option<&'a RcType> {
+        use pikelet_core::syntax::LevelShift;
+
+        match ty.free_var_app() {
+            Some((fv, LevelShift(0), &[ref ty])) if *fv == self.globals.var_io => Some(ty),
+            Some(_) | None => None,
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn trace(&self) -> Option<&Rc<Trace>> {
+        self.trace.as_ref()
+    }
+
+    pub fn set_trace(&mut self, trace: Option<Rc<Trace>>) {
+        self.trace = trace;
+    }
+
     pub fn get_import(&self, name: &str) -> Option<&(Import, RcType)> {
         self.imports.get(name)
     }
 
+    /// The number of local variables that have been bound, not counting the
+    /// ones in the built-in prelude
+    pub fn locals(&self) -> usize {
+        self.declarations.len() - self.prelude_declarations
+    }
+
     pub fn get_declaration(&self, free_var: &FreeVar<String>) -> Option<&RcType> {
         self.declarations.get(free_var)
     }
 
     pub fn get_definition(&self, free_var: &FreeVar<String>) -> Option<&RcTerm> {
-        self.definitions.get(free_var)
+        self.definitions.get(free_var).map(|definition| &definition.term)
+    }
+
+    /// The opened records that provide a field with the given label
+    pub fn get_opens(&self, label: &Label) -> impl Iterator<Item = &Open> {
+        let opens = self.opens.get(&label.0).map_or(&[][..], Vec::as_slice);
+        opens.iter().map(|open| &**open)
     }
 
     pub fn insert_import(&mut self, name: String, import: Import, ty: RcType) {
         self.imports.insert(name, (import, ty));
     }
 
+    pub fn insert_open(&mut self, open: Open) {
+        let open = Rc::new(open);
+        for label in &open.labels {
+            let mut opens = self.opens.get(&label.0).cloned().unwrap_or_default();
+            opens.push(open.clone());
+            self.opens.insert(label.0.clone(), opens);
+        }
+    }
+
     pub fn insert_declaration(&mut self, free_var: FreeVar<String>, ty: RcType) {
         self.resugar_env.on_binder(&Binder(free_var.clone()));
         self.declarations.insert(free_var, ty);
     }
 
+    /// Insert a definition, replacing any previous definition of the same
+    /// variable, along with its cached normal form
     pub fn insert_definition(&mut self, free_var: FreeVar<String>, term: RcTerm) {
         self.resugar_env.on_binder(&Binder(free_var.clone()));
-        self.definitions.insert(free_var, term);
+        let nf = Rc::new(RefCell::new(None));
+        self.definitions.insert(free_var, Definition { term, nf });
     }
 }
 
@@ -576,6 +780,22 @@ impl nbe::Env for Context {
     }
 
     fn get_definition(&self, free_var: &FreeVar<String>) -> Option<&RcTerm> {
-        self.definitions.get(free_var)
+        self.definitions.get(free_var).map(|definition| &definition.term)
+    }
+
+    fn get_definition_nf(&self, free_var: &FreeVar<String>) -> Option<RcValue> {
+        let definition = self.definitions.get(free_var)?;
+        let nf = definition.nf.borrow();
+        nf.clone()
+    }
+
+    fn cache_definition_nf(&self, free_var: &FreeVar<String>, value: &RcValue) {
+        if let Some(definition) = self.definitions.get(free_var) {
+            *definition.nf.borrow_mut() = Some(value.clone());
+        }
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
     }
 }
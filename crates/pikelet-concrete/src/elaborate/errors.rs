@@ -5,6 +5,7 @@ use codespan_reporting::{Diagnostic, Label};
 use moniker::{Binder, FreeVar, Var};
 use failure::Fail;
 
+use pikelet_core::limits::LimitExceeded;
 use pikelet_core::nbe::NbeError;
 use pikelet_core::syntax;
 
@@ -110,8 +111,6 @@ pub enum TypeError {
     },
     #[fail(display = "Ambiguous integer literal")]
     AmbiguousIntLiteral { span: ByteSpan },
-    #[fail(display = "Ambiguous floating point literal")]
-    AmbiguousFloatLiteral { span: ByteSpan },
     #[fail(display = "Empty case expressions need type annotations.")]
     AmbiguousEmptyCase { span: ByteSpan },
     #[fail(display = "Unable to elaborate hole, expected: `{:?}`", expected)]
@@ -183,11 +182,37 @@ pub enum TypeError {
         found_size: u64,
         expected_size: u64,
     },
+    #[fail(
+        display = "Ambiguous name: `{}` is provided by more than one open",
+        name
+    )]
+    AmbiguousName {
+        span: ByteSpan,
+        name: syntax::Label,
+        /// The spans of the records that were opened
+        opens: Vec<ByteSpan>,
+    },
+    #[fail(display = "Found `{}` but a record was expected", found)]
+    OpenedNonRecord {
+        span: ByteSpan,
+        found: Box<concrete::Term>,
+    },
+    #[fail(display = "Term too large: {}", limit)]
+    TermTooLarge {
+        /// The span of the term that was being elaborated when the limit was
+        /// exceeded, if known
+        span: Option<ByteSpan>,
+        limit: LimitExceeded,
+    },
     #[fail(display = "Internal error - this is a bug! {}", _0)]
     Internal(#[cause] InternalError),
 }
 
 impl TypeError {
+    /// The code given to the diagnostics of holes that could not be
+    /// elaborated, allowing front-ends to show them as goals
+    pub const HOLE_CODE: &'static str = "hole";
+
     /// Convert the error into a diagnostic message
     pub fn to_diagnostic(&self) -> Diagnostic {
         match *self {
@@ -279,10 +304,6 @@ impl TypeError {
                 "ambiguous integer literal",
             )
             .with_label(Label::new_primary(span).with_message("type annotation needed here")),
-            TypeError::AmbiguousFloatLiteral { span } => Diagnostic::new_error(
-                "ambiguous floating point literal",
-            )
-            .with_label(Label::new_primary(span).with_message("type annotation needed here")),
             TypeError::AmbiguousEmptyCase { span } => Diagnostic::new_error(
                 "empty case expressions need type annotations",
             )
@@ -292,6 +313,7 @@ impl TypeError {
                 expected: None,
                 ..
             } => Diagnostic::new_error("unable to elaborate hole")
+                .with_code(TypeError::HOLE_CODE)
                 .with_label(Label::new_primary(span).with_message("the hole")),
             TypeError::UnableToElaborateHole {
                 span,
@@ -301,6 +323,7 @@ impl TypeError {
                 "unable to elaborate hole - expected: `{}`",
                 expected,
             ))
+            .with_code(TypeError::HOLE_CODE)
             .with_label(Label::new_primary(span).with_message("the hole")),
             TypeError::UnexpectedFunction {
                 span, ref expected, ..
@@ -374,6 +397,48 @@ impl TypeError {
             .with_label(
                 Label::new_primary(span).with_message(format!("record with {} fields", found_size)),
             ),
+            TypeError::AmbiguousName {
+                span,
+                ref name,
+                ref opens,
+            } => {
+                let diagnostic = Diagnostic::new_error(format!(
+                    "`{}` is provided by more than one open record",
+                    name,
+                ))
+                .with_label(Label::new_primary(span).with_message("the ambiguous name"));
+
+                opens.iter().fold(diagnostic, |diagnostic, &span| {
+                    let label = Label::new_secondary(span);
+                    diagnostic.with_label(label.with_message("provided by this record"))
+                })
+            },
+            TypeError::OpenedNonRecord { span, ref found } => Diagnostic::new_error(format!(
+                "expected a record to open, but found a term of type `{}`",
+                found,
+            ))
+            .with_label(Label::new_primary(span).with_message("the opened term")),
+            TypeError::TermTooLarge { span, limit } => {
+                let diagnostic = Diagnostic::new_error(format!("term too large: {}", limit));
+                match span {
+                    None => diagnostic,
+                    Some(span) => diagnostic.with_label(
+                        Label::new_primary(span).with_message("while elaborating this term"),
+                    ),
+                }
+            },
+        }
+    }
+
+    /// Record the span of the term that was being elaborated, if the error
+    /// was produced without one
+    pub(super) fn within(self, term_span: ByteSpan) -> TypeError {
+        match self {
+            TypeError::TermTooLarge { span: None, limit } => TypeError::TermTooLarge {
+                span: Some(term_span),
+                limit,
+            },
+            error => error,
         }
     }
 }
@@ -386,6 +451,9 @@ impl From<InternalError> for TypeError {
 
 impl From<NbeError> for TypeError {
     fn from(src: NbeError) -> TypeError {
-        TypeError::from(InternalError::from(src))
+        match src {
+            NbeError::TooLarge(limit) => TypeError::TermTooLarge { span: None, limit },
+            src => TypeError::from(InternalError::from(src)),
+        }
     }
 }
@@ -10,15 +10,18 @@ use moniker::{Binder, BoundPattern, BoundTerm, Embed, FreeVar, Nest, Scope, Var}
 use pikelet_core::nbe;
 use pikelet_core::syntax::core::{Pattern, RcPattern, RcTerm, Term};
 use pikelet_core::syntax::domain::{RcType, RcValue, Value};
-use pikelet_core::syntax::{Level, Literal};
+use pikelet_core::syntax::{Label, Level, LevelShift, Literal};
 
-use crate::syntax::raw;
+use crate::resugar::OPENED_NAME;
+use crate::syntax::{raw, Unfold};
 
 mod context;
 mod errors;
+mod trace;
 
-pub use self::context::{Context, Globals};
+pub use self::context::{Context, Globals, MemoryUsage, Open};
 pub use self::errors::{InternalError, TypeError};
+pub use self::trace::{Trace, TraceStep};
 
 /// Returns true if `ty1` is a subtype of `ty2`
 fn is_subtype(context: &Context, ty1: &RcType, ty2: &RcType) -> bool {
@@ -72,7 +75,10 @@ fn is_subtype(context: &Context, ty1: &RcType, ty2: &RcType) -> bool {
 
 /// Ensures that the given term is a universe, returning the level of that
 /// universe and its elaborated form.
-fn infer_universe(context: &Context, raw_term: &raw::RcTerm) -> Result<(RcTerm, Level), TypeError> {
+pub fn infer_universe(
+    context: &Context,
+    raw_term: &raw::RcTerm,
+) -> Result<(RcTerm, Level), TypeError> {
     let (term, ty) = infer_term(context, raw_term)?;
     match *ty {
         Value::Universe(level) => Ok((term, level)),
@@ -120,17 +126,29 @@ fn check_literal(
 
 /// Synthesize the type of a literal, returning the elaborated literal and the
 /// inferred type if successful
+///
+/// Numeric literals can be checked against any of the numeric types, so
+/// without an expected type we fall back to `S32` for integer literals and
+/// `F64` for floating point literals. Integer literals that are too large to
+/// fit in an `S32` fall back to `S64` instead, and those that are too large
+/// for an `S64` need a type annotation.
 fn infer_literal(
     context: &Context,
     raw_literal: &raw::Literal,
 ) -> Result<(Literal, RcType), TypeError> {
-    use pikelet_core::syntax::Literal::{Char, String};
+    use pikelet_core::syntax::Literal::{Char, String, F64, S32, S64};
 
     match *raw_literal {
         raw::Literal::String(_, ref val) => Ok((String(val.clone()), context.string().clone())),
         raw::Literal::Char(_, val) => Ok((Char(val), context.char().clone())),
+        raw::Literal::Int(_, v, _) if v <= i32::MAX as u64 => {
+            Ok((S32(v as i32), context.s32().clone()))
+        },
+        raw::Literal::Int(_, v, _) if v <= i64::MAX as u64 => {
+            Ok((S64(v as i64), context.s64().clone()))
+        },
         raw::Literal::Int(span, _, _) => Err(TypeError::AmbiguousIntLiteral { span }),
-        raw::Literal::Float(span, _, _) => Err(TypeError::AmbiguousFloatLiteral { span }),
+        raw::Literal::Float(_, v, _) => Ok((F64(v), context.f64().clone())),
     }
 }
 
@@ -226,6 +244,27 @@ pub fn check_term(
     context: &Context,
     raw_term: &raw::RcTerm,
     expected_ty: &RcType,
+) -> Result<RcTerm, TypeError> {
+    let result = match context.trace() {
+        None => check_term_step(context, raw_term, expected_ty),
+        Some(trace) => {
+            let rule = trace::check_rule(&raw_term.inner);
+            trace.enter(rule, context.locals(), raw_term, || {
+                Some(context.resugar(expected_ty))
+            });
+            let result = check_term_step(context, raw_term, expected_ty);
+            trace.exit(result.as_ref().err());
+            result
+        },
+    };
+
+    result.map_err(|error| error.within(raw_term.span()))
+}
+
+fn check_term_step(
+    context: &Context,
+    raw_term: &raw::RcTerm,
+    expected_ty: &RcType,
 ) -> Result<RcTerm, TypeError> {
     match (&*raw_term.inner, &*expected_ty.inner) {
         (&raw::Term::Literal(ref raw_literal), _) => {
@@ -377,11 +416,103 @@ pub fn check_term(
     }
 }
 
+/// Project a field out of an elaborated term, returning `None` if its type
+/// does not contain a field with the given label
+fn infer_proj(
+    context: &Context,
+    expr: &RcTerm,
+    ty: &RcType,
+    label: &Label,
+    shift: LevelShift,
+) -> Result<Option<(RcTerm, RcType)>, TypeError> {
+    if let Value::RecordType(ref scope) = *ty.inner {
+        let (fields, ()) = scope.clone().unbind();
+        let mut mappings = vec![];
+
+        for (current_label, Binder(free_var), Embed(current_ann)) in fields.unnest() {
+            if current_label == *label {
+                let expr = RcTerm::from(Term::RecordProj(expr.clone(), current_label, shift));
+                let mut ty = nbe::nf_term(context, &current_ann.substs(&mappings))?;
+                ty.shift_universes(shift);
+
+                return Ok(Some((expr, ty)));
+            } else {
+                mappings.push((
+                    free_var,
+                    // NOTE: Not sure if we should be shifting here...
+                    RcTerm::from(Term::RecordProj(expr.clone(), current_label, shift)),
+                ));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Look up a name that is not otherwise in scope in the records that have
+/// been opened, projecting it out of the record that provides it
+fn infer_opened(
+    context: &Context,
+    span: ByteSpan,
+    free_var: &FreeVar<String>,
+    shift: LevelShift,
+) -> Result<(RcTerm, RcType), TypeError> {
+    let undefined_name = || TypeError::UndefinedName {
+        span,
+        free_var: free_var.clone(),
+    };
+    let label = match free_var.pretty_name {
+        Some(ref name) => Label(name.clone()),
+        None => return Err(undefined_name()),
+    };
+
+    // Opening the same record more than once is not ambiguous
+    let mut opens = Vec::<&Open>::new();
+    for open in context.get_opens(&label) {
+        let seen = (opens.iter()).any(|other| RcTerm::term_eq(&other.term, &open.term));
+        if !seen {
+            opens.push(open);
+        }
+    }
+
+    match opens[..] {
+        [] => Err(undefined_name()),
+        [open] => {
+            let expr = RcTerm::from(Term::Var(Var::Free(open.var.clone()), LevelShift(0)));
+            let proj = infer_proj(context, &expr, &open.ty, &label, shift)?;
+            proj.ok_or_else(undefined_name)
+        },
+        _ => Err(TypeError::AmbiguousName {
+            span,
+            name: label,
+            opens: opens.iter().map(|open| open.span).collect(),
+        }),
+    }
+}
+
 /// Synthesize the type of a term, returning the elaborated term and the
 /// inferred type if successful
 pub fn infer_term(
     context: &Context,
     raw_term: &raw::RcTerm,
+) -> Result<(RcTerm, RcType), TypeError> {
+    let result = match context.trace() {
+        None => infer_term_step(context, raw_term),
+        Some(trace) => {
+            let rule = trace::infer_rule(&raw_term.inner);
+            trace.enter(rule, context.locals(), raw_term, || None);
+            let result = infer_term_step(context, raw_term);
+            trace.exit(result.as_ref().err());
+            result
+        },
+    };
+
+    result.map_err(|error| error.within(raw_term.span()))
+}
+
+fn infer_term_step(
+    context: &Context,
+    raw_term: &raw::RcTerm,
 ) -> Result<(RcTerm, RcType), TypeError> {
     use std::cmp;
 
@@ -427,10 +558,7 @@ pub fn infer_term(
 
                     Ok((RcTerm::from(Term::Var(var.clone(), shift)), ty))
                 },
-                None => Err(TypeError::UndefinedName {
-                    span,
-                    free_var: free_var.clone(),
-                }),
+                None => infer_opened(context, span, free_var, shift),
             },
 
             // We should always be substituting bound variables with fresh
@@ -505,49 +633,147 @@ pub fn infer_term(
         raw::Term::Let(_, ref raw_scope) => {
             let (raw_fields, raw_body) = raw_scope.clone().unbind();
 
-            let (term, ty) = {
-                let mut context = context.clone();
-                let bindings = raw_fields
-                    .unnest()
-                    .into_iter()
-                    .map(|(Binder(free_var), Embed(raw_term))| {
-                        let (term, term_ty) = infer_term(&context, &raw_term)?;
-
+            let mut context = context.clone();
+            let mut bindings = Vec::new();
+            // Definitions that are substituted into the elaborated term
+            let mut inlined = Vec::new();
+            // Definitions that are hidden from the context while checking
+            let mut opaque = Vec::new();
+
+            for (Binder(free_var), unfold, Embed(raw_term)) in raw_fields.unnest() {
+                let (term, term_ty) = infer_term(&context, &raw_term)?;
+                let term = term.substs(&inlined);
+
+                context.insert_declaration(free_var.clone(), term_ty);
+                match unfold {
+                    Unfold::Default => {
+                        context.insert_definition(free_var.clone(), term.clone());
+                        bindings.push((Binder(free_var), Embed(term)));
+                    },
+                    Unfold::Inline => {
                         context.insert_definition(free_var.clone(), term.clone());
-                        context.insert_declaration(free_var.clone(), term_ty);
+                        inlined.push((free_var, term));
+                    },
+                    Unfold::Never => {
+                        opaque.push((free_var.clone(), term.clone()));
+                        bindings.push((Binder(free_var), Embed(term)));
+                    },
+                }
+            }
 
-                        Ok((Binder(free_var), Embed(term)))
-                    })
-                    .collect::<Result<_, TypeError>>()?;
+            let (body, ty) = infer_term(&context, &raw_body)?;
+            let body = body.substs(&inlined);
+            let term = match bindings[..] {
+                [] => body,
+                _ => RcTerm::from(Term::Let(Scope::new(Nest::new(bindings), body))),
+            };
 
-                let (body, ty) = infer_term(&context, &raw_body)?;
-                let term = RcTerm::from(Term::Let(Scope::new(Nest::new(bindings), body)));
+            // The opaque definitions are about to go out of scope, so we need
+            // to unfold any references to them that remain in the type
+            let ty = match opaque[..] {
+                [] => ty,
+                _ => {
+                    for (free_var, term) in opaque {
+                        context.insert_definition(free_var, term);
+                    }
+                    nbe::nf_term(&context, &RcTerm::from(&*ty))?
+                },
+            };
+
+            Ok((term, ty))
+        },
 
-                (term, ty)
+        // I-OPEN
+        raw::Term::Open(_, ref raw_module, ref using, ref raw_body) => {
+            let (module, module_ty) = infer_term(context, raw_module)?;
+            let labels = match *module_ty.inner {
+                Value::RecordType(ref scope) => (scope.unsafe_pattern.unsafe_patterns.iter())
+                    .map(|&(ref label, _, _)| label.clone())
+                    .collect::<Vec<_>>(),
+                _ => {
+                    return Err(TypeError::OpenedNonRecord {
+                        span: raw_module.span(),
+                        found: Box::new(context.resugar(&module_ty)),
+                    });
+                },
+            };
+
+            let labels = match *using {
+                None => labels,
+                Some(ref using) => {
+                    for &(label_span, ref label) in using {
+                        if !labels.contains(label) {
+                            return Err(TypeError::NoFieldInType {
+                                label_span,
+                                expected_label: label.clone(),
+                                found: Box::new(context.resugar(&module_ty)),
+                            });
+                        }
+                    }
+                    using.iter().map(|&(_, ref label)| label.clone()).collect()
+                },
+            };
+
+            // Bind the record to a variable, so that it is only evaluated
+            // once, no matter how many of its fields are used
+            let var = FreeVar::fresh_named(OPENED_NAME);
+            let mut context = context.clone();
+            context.insert_declaration(var.clone(), module_ty.clone());
+            context.insert_definition(var.clone(), module.clone());
+            context.insert_open(Open {
+                span: raw_module.span(),
+                var: var.clone(),
+                term: module.clone(),
+                ty: module_ty,
+                labels,
+            });
+
+            let (body, ty) = infer_term(&context, raw_body)?;
+            let term = if body.free_vars().contains(&var) {
+                let bindings = Nest::new(vec![(Binder(var), Embed(module))]);
+                RcTerm::from(Term::Let(Scope::new(bindings, body)))
+            } else {
+                body
             };
 
             Ok((term, ty))
         },
 
         // I-APP
-        raw::Term::FunApp(ref raw_head, ref raw_arg) => {
-            let (head, head_ty) = infer_term(context, raw_head)?;
+        raw::Term::FunApp(_, _) => {
+            // Work along the spine of the application in a loop, rather than
+            // recursing once for each argument
+            let mut raw_apps = Vec::new();
+            let mut raw_head = raw_term;
+            while let raw::Term::FunApp(ref raw_fun, ref raw_arg) = *raw_head.inner {
+                raw_apps.push((raw_head, raw_fun, raw_arg));
+                raw_head = raw_fun;
+            }
 
-            match *head_ty {
-                Value::FunType(ref scope) => {
-                    let ((Binder(free_var), Embed(ann)), body) = scope.clone().unbind();
+            let (mut head, mut head_ty) = infer_term(context, raw_head)?;
+
+            for (raw_app, raw_fun, raw_arg) in raw_apps.into_iter().rev() {
+                let scope = match *head_ty {
+                    Value::FunType(ref scope) => scope.clone(),
+                    _ => {
+                        return Err(TypeError::ArgAppliedToNonFunction {
+                            fn_span: raw_fun.span(),
+                            arg_span: raw_arg.span(),
+                            found: Box::new(context.resugar(&head_ty)),
+                        });
+                    },
+                };
+                let ((Binder(free_var), Embed(ann)), body) = scope.unbind();
 
-                    let arg = check_term(context, raw_arg, &ann)?;
-                    let body = nbe::nf_term(context, &body.substs(&[(free_var, arg.clone())]))?;
+                let arg = check_term(context, raw_arg, &ann)?;
+                let body = body.substs(&[(free_var, arg.clone())]);
 
-                    Ok((RcTerm::from(Term::FunApp(head, arg)), body))
-                },
-                _ => Err(TypeError::ArgAppliedToNonFunction {
-                    fn_span: raw_head.span(),
-                    arg_span: raw_arg.span(),
-                    found: Box::new(context.resugar(&head_ty)),
-                }),
+                head_ty = nbe::nf_term(context, &body)
+                    .map_err(|error| TypeError::from(error).within(raw_app.span()))?;
+                head = RcTerm::from(Term::FunApp(head, arg));
             }
+
+            Ok((head, head_ty))
         },
 
         // I-RECORD-TYPE, I-EMPTY-RECORD-TYPE
@@ -608,32 +834,14 @@ pub fn infer_term(
         raw::Term::RecordProj(_, ref expr, label_span, ref label, shift) => {
             let (expr, ty) = infer_term(context, expr)?;
 
-            if let Value::RecordType(ref scope) = *ty.inner {
-                let (fields, ()) = scope.clone().unbind();
-                let mut mappings = vec![];
-
-                for (current_label, Binder(free_var), Embed(current_ann)) in fields.unnest() {
-                    if current_label == *label {
-                        let expr = RcTerm::from(Term::RecordProj(expr, current_label, shift));
-                        let mut ty = nbe::nf_term(context, &current_ann.substs(&mappings))?;
-                        ty.shift_universes(shift);
-
-                        return Ok((expr, ty));
-                    } else {
-                        mappings.push((
-                            free_var,
-                            // NOTE: Not sure if we should be shifting here...
-                            RcTerm::from(Term::RecordProj(expr.clone(), current_label, shift)),
-                        ));
-                    }
-                }
+            match infer_proj(context, &expr, &ty, label, shift)? {
+                Some((expr, ty)) => Ok((expr, ty)),
+                None => Err(TypeError::NoFieldInType {
+                    label_span,
+                    expected_label: label.clone(),
+                    found: Box::new(context.resugar(&ty)),
+                }),
             }
-
-            Err(TypeError::NoFieldInType {
-                label_span,
-                expected_label: label.clone(),
-                found: Box::new(context.resugar(&ty)),
-            })
         },
 
         // I-CASE
@@ -682,3 +890,38 @@ pub fn infer_term(
         raw::Term::ArrayIntro(span, _) => Err(TypeError::AmbiguousArrayLiteral { span }),
     }
 }
+
+/// The type of an elaborated term, as it was written in the annotations and
+/// let bindings of the term, before it was normalized
+///
+/// This falls back to the normalized type, `ty`, that was inferred for the
+/// term if no better type can be found.
+pub fn elaborated_type(term: &RcTerm, ty: &RcType) -> RcTerm {
+    match *term.inner {
+        Term::Ann(_, ref ann) => ann.clone(),
+        Term::FunIntro(ref scope) => match *ty.inner {
+            Value::FunType(ref ty_scope) => {
+                let ((binder, Embed(ann)), body, _, body_ty) =
+                    Scope::unbind2(scope.clone(), ty_scope.clone());
+                let body_ty = elaborated_type(&body, &body_ty);
+
+                RcTerm::from(Term::FunType(Scope::new((binder, Embed(ann)), body_ty)))
+            },
+            _ => RcTerm::from(&**ty),
+        },
+        Term::Let(ref scope) => {
+            let (bindings, body) = scope.clone().unbind();
+            let body_ty = elaborated_type(&body, ty);
+
+            // Only keep the bindings around if they are used in the type
+            let free_vars = body_ty.free_vars();
+            let is_used = (bindings.binders().iter()).any(|binder| free_vars.contains(&binder.0));
+            if is_used {
+                RcTerm::from(Term::Let(Scope::new(bindings, body_ty)))
+            } else {
+                body_ty
+            }
+        },
+        _ => RcTerm::from(&**ty),
+    }
+}
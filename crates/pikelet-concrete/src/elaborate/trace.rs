@@ -0,0 +1,178 @@
+//! Tracing of the steps taken during elaboration
+//!
+//! This is useful for figuring out why a term fails to type check, without
+//! resorting to sprinkling `println!`s throughout `check_term` and
+//! `infer_term`.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use crate::syntax::{concrete, raw};
+
+use super::TypeError;
+
+/// A single step taken during elaboration
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceStep {
+    /// We started checking or inferring a term
+    Enter {
+        /// How deeply the step is nested inside other checking and inference
+        /// steps
+        depth: usize,
+        /// The name of the typing rule that was applied
+        rule: &'static str,
+        /// The number of local variables bound in the context
+        locals: usize,
+        /// The term being elaborated
+        term: String,
+        /// The expected type, if we were checking the term
+        expected: Option<String>,
+    },
+    /// Elaboration of a term failed
+    Failed {
+        /// How deeply the step is nested inside other checking and inference
+        /// steps
+        depth: usize,
+        /// The error message
+        message: String,
+    },
+}
+
+impl TraceStep {
+    pub fn depth(&self) -> usize {
+        match *self {
+            TraceStep::Enter { depth, .. } | TraceStep::Failed { depth, .. } => depth,
+        }
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:width$}", "", width = self.depth() * 2)?;
+        match *self {
+            TraceStep::Enter {
+                rule,
+                locals,
+                ref term,
+                expected: Some(ref expected),
+                ..
+            } => write!(
+                f,
+                "[{}] (locals: {}) {} <= {}",
+                rule, locals, term, expected
+            ),
+            TraceStep::Enter {
+                rule,
+                locals,
+                ref term,
+                expected: None,
+                ..
+            } => write!(f, "[{}] (locals: {}) {} =>", rule, locals, term),
+            TraceStep::Failed { ref message, .. } => write!(f, "failed: {}", message),
+        }
+    }
+}
+
+/// A recorder for the steps taken during elaboration
+///
+/// Steps that are nested more deeply than `max_depth` are not recorded, to
+/// keep the traces of large terms readable.
+#[derive(Debug)]
+pub struct Trace {
+    max_depth: usize,
+    depth: Cell<usize>,
+    steps: RefCell<Vec<TraceStep>>,
+}
+
+impl Trace {
+    pub fn new(max_depth: usize) -> Trace {
+        Trace {
+            max_depth,
+            depth: Cell::new(0),
+            steps: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Take the steps that have been recorded so far, leaving the trace empty
+    pub fn take_steps(&self) -> Vec<TraceStep> {
+        self.steps.replace(Vec::new())
+    }
+
+    /// Record the start of a checking or inference step
+    ///
+    /// The expected type is only computed if the step is going to be
+    /// recorded, as resugaring it can be expensive.
+    pub(super) fn enter(
+        &self,
+        rule: &'static str,
+        locals: usize,
+        term: &raw::RcTerm,
+        expected: impl FnOnce() -> Option<concrete::Term>,
+    ) {
+        let depth = self.depth.get();
+        if depth < self.max_depth {
+            self.steps.borrow_mut().push(TraceStep::Enter {
+                depth,
+                rule,
+                locals,
+                term: term.to_string(),
+                expected: expected().map(|expected| expected.to_string()),
+            });
+        }
+        self.depth.set(depth + 1);
+    }
+
+    /// Record the end of a checking or inference step
+    pub(super) fn exit(&self, error: Option<&TypeError>) {
+        let depth = self.depth.get() - 1;
+        self.depth.set(depth);
+        match error {
+            Some(error) if depth < self.max_depth => {
+                self.steps.borrow_mut().push(TraceStep::Failed {
+                    depth,
+                    message: error.to_string(),
+                });
+            },
+            Some(_) | None => {},
+        }
+    }
+}
+
+/// The name of the rule that will be used when checking a term
+pub(super) fn check_rule(raw_term: &raw::Term) -> &'static str {
+    match *raw_term {
+        raw::Term::Literal(_) => "C-LITERAL",
+        raw::Term::FunIntro(_, _) => "C-LAM",
+        raw::Term::RecordIntro(_, _) => "C-RECORD",
+        raw::Term::Case(_, _, _) => "C-CASE",
+        raw::Term::ArrayIntro(_, _) => "C-ARRAY",
+        raw::Term::Hole(_) => "C-HOLE",
+        _ => "C-CONV",
+    }
+}
+
+/// The name of the rule that will be used when inferring the type of a term
+pub(super) fn infer_rule(raw_term: &raw::Term) -> &'static str {
+    match *raw_term {
+        raw::Term::Ann(_, _) => "I-ANN",
+        raw::Term::Universe(_, _) => "I-TYPE",
+        raw::Term::Hole(_) => "I-HOLE",
+        raw::Term::Literal(_) => "I-LITERAL",
+        raw::Term::Var(_, _, _) => "I-VAR",
+        raw::Term::Import(_, _, _) => "I-IMPORT",
+        raw::Term::FunType(_, _) => "I-PI",
+        raw::Term::FunIntro(_, _) => "I-LAM",
+        raw::Term::Let(_, _) => "I-LET",
+        raw::Term::Open(_, _, _, _) => "I-OPEN",
+        raw::Term::FunApp(_, _) => "I-APP",
+        raw::Term::RecordType(_, _) => "I-RECORD-TYPE",
+        raw::Term::RecordIntro(_, _) => "I-RECORD",
+        raw::Term::RecordProj(_, _, _, _, _) => "I-PROJ",
+        raw::Term::Case(_, _, _) => "I-CASE",
+        raw::Term::ArrayIntro(_, _) => "I-ARRAY",
+    }
+}
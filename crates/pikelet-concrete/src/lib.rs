@@ -2,6 +2,7 @@
 
 pub mod desugar;
 pub mod elaborate;
+pub mod lint;
 pub mod parse;
 pub mod resugar;
 pub mod syntax;
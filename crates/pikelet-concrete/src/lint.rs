@@ -0,0 +1,647 @@
+//! Lints that are checked over the concrete syntax
+//!
+//! Unlike the errors produced during desugaring and elaboration, the warnings
+//! produced here do not prevent a program from being type checked. They can be
+//! suppressed on a per-item basis using `allow` attributes:
+//!
+//! ```text
+//! #[allow(unused-binders)]
+//! const a b (x : a) (y : b) = x;
+//! ```
+
+use codespan::{ByteIndex, ByteOffset, ByteSpan};
+use codespan_reporting::{Diagnostic, Label};
+use failure::Fail;
+use std::fmt;
+
+use crate::syntax::concrete;
+
+/// The lints that can be allowed using attributes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// Function parameters that are never referred to in their body
+    UnusedBinders,
+    /// Binders that shadow a local binding of the same name
+    Shadowing,
+    /// Imports that are bound to a name that is never referred to
+    UnusedImports,
+    /// References to items that are marked with a `deprecated` attribute
+    Deprecated,
+}
+
+impl Lint {
+    /// All of the available lints
+    pub const ALL: &'static [Lint] = &[
+        Lint::UnusedBinders,
+        Lint::Shadowing,
+        Lint::UnusedImports,
+        Lint::Deprecated,
+    ];
+
+    /// The name of the lint, as used in attributes and diagnostic codes
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedBinders => "unused-binders",
+            Lint::Shadowing => "shadowing",
+            Lint::UnusedImports => "unused-imports",
+            Lint::Deprecated => "deprecated",
+        }
+    }
+
+    /// Look up a lint by its name
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().cloned().find(|lint| lint.name() == name)
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A warning produced while linting
+#[derive(Debug, Fail, Clone, PartialEq)]
+pub enum Warning {
+    #[fail(display = "Unused binder: `{}`", name)]
+    UnusedBinder { span: ByteSpan, name: String },
+    #[fail(display = "Name shadows an existing binding: `{}`", name)]
+    ShadowedName {
+        span: ByteSpan,
+        original_span: ByteSpan,
+        name: String,
+    },
+    #[fail(display = "Unused import: `{}`", path)]
+    UnusedImport {
+        span: ByteSpan,
+        name: String,
+        path: String,
+    },
+    #[fail(display = "Use of deprecated item: `{}`", name)]
+    Deprecated {
+        span: ByteSpan,
+        name: String,
+        message: Option<String>,
+    },
+    #[fail(display = "Unknown attribute: `{}`", name)]
+    UnknownAttribute { span: ByteSpan, name: String },
+    #[fail(display = "Malformed attribute: `{}`", name)]
+    MalformedAttribute { span: ByteSpan, name: String },
+    #[fail(display = "Unknown lint: `{}`", name)]
+    UnknownLint { span: ByteSpan, name: String },
+}
+
+impl Warning {
+    /// The lint that produced this warning, if it can be allowed
+    pub fn lint(&self) -> Option<Lint> {
+        match *self {
+            Warning::UnusedBinder { .. } => Some(Lint::UnusedBinders),
+            Warning::ShadowedName { .. } => Some(Lint::Shadowing),
+            Warning::UnusedImport { .. } => Some(Lint::UnusedImports),
+            Warning::Deprecated { .. } => Some(Lint::Deprecated),
+            Warning::UnknownAttribute { .. }
+            | Warning::MalformedAttribute { .. }
+            | Warning::UnknownLint { .. } => None,
+        }
+    }
+
+    /// Convert the warning into a diagnostic message
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match *self {
+            Warning::UnusedBinder { span, ref name } => {
+                Diagnostic::new_warning(format!("unused binder `{}`", name))
+                    .with_label(Label::new_primary(span).with_message("never used"))
+            },
+            Warning::ShadowedName {
+                span,
+                original_span,
+                ref name,
+            } => Diagnostic::new_warning(format!("`{}` shadows an existing binding", name))
+                .with_label(Label::new_primary(span).with_message("the shadowing binder"))
+                .with_label(
+                    Label::new_secondary(original_span).with_message("the original binder"),
+                ),
+            Warning::UnusedImport {
+                span,
+                ref name,
+                ref path,
+            } => Diagnostic::new_warning(format!("unused import {:?}", path)).with_label(
+                Label::new_primary(span).with_message(format!("`{}` is never used", name)),
+            ),
+            Warning::Deprecated {
+                span,
+                ref name,
+                ref message,
+            } => {
+                let label = match *message {
+                    None => Label::new_primary(span),
+                    Some(ref message) => Label::new_primary(span).with_message(message.clone()),
+                };
+                Diagnostic::new_warning(format!("use of deprecated item `{}`", name))
+                    .with_label(label)
+            },
+            Warning::UnknownAttribute { span, ref name } => {
+                Diagnostic::new_warning(format!("unknown attribute `{}`", name))
+                    .with_label(Label::new_primary(span))
+            },
+            Warning::MalformedAttribute { span, ref name } => {
+                let message = match attribute_template(name) {
+                    Some(template) => format!("expected `{}`", template),
+                    None => "unexpected arguments".to_owned(),
+                };
+                Diagnostic::new_warning(format!("malformed `{}` attribute", name))
+                    .with_label(Label::new_primary(span).with_message(message))
+            },
+            Warning::UnknownLint { span, ref name } => {
+                Diagnostic::new_warning(format!("unknown lint `{}`", name))
+                    .with_label(Label::new_primary(span))
+            },
+        };
+
+        match self.lint() {
+            None => diagnostic,
+            Some(lint) => diagnostic.with_code(lint.name()),
+        }
+    }
+}
+
+/// The syntax expected for each of the known attributes
+fn attribute_template(name: &str) -> Option<&'static str> {
+    match name {
+        "allow" => Some("#[allow(<lint>, ...)]"),
+        "deprecated" => Some("#[deprecated \"<message>\"]"),
+        "inline" => Some("#[inline]"),
+        "no_unfold" => Some("#[no_unfold]"),
+        _ => None,
+    }
+}
+
+/// Check a term for lint warnings
+pub fn check_term(term: &concrete::Term) -> Vec<Warning> {
+    let mut linter = Linter {
+        bindings: Vec::new(),
+        allowed: Vec::new(),
+        warnings: Vec::new(),
+    };
+    linter.term(term);
+    linter.warnings
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum BindingKind {
+    /// Function parameters, which should be used in the body
+    Param,
+    /// Items that were bound to an import
+    Import(String),
+    /// Record labels that were implicitly used as binders
+    Label,
+    /// Other binders that we don't warn about if they are unused
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    name: String,
+    span: ByteSpan,
+    kind: BindingKind,
+    used: bool,
+    /// Set if the item was marked as deprecated, along with the optional
+    /// message given in the attribute
+    deprecated: Option<Option<String>>,
+    /// The lints that were allowed at the point where this binding was introduced
+    allowed: Vec<Lint>,
+}
+
+struct Linter {
+    /// The local bindings that are currently in scope, innermost last
+    bindings: Vec<Binding>,
+    /// The lints that have been allowed by the attributes of enclosing items
+    allowed: Vec<Lint>,
+    warnings: Vec<Warning>,
+}
+
+/// Names beginning with an underscore are exempt from lints
+fn is_exempt(name: &str) -> bool {
+    name.starts_with('_')
+}
+
+fn import_path(term: &concrete::Term) -> Option<&str> {
+    match *term {
+        concrete::Term::Import(_, _, ref path) => Some(path),
+        concrete::Term::Parens(_, ref term) | concrete::Term::Ann(ref term, _) => import_path(term),
+        _ => None,
+    }
+}
+
+impl Linter {
+    fn is_allowed(&self, lint: Lint) -> bool {
+        self.allowed.contains(&lint)
+    }
+
+    fn warn(&mut self, warning: Warning) {
+        match warning.lint() {
+            Some(lint) if self.is_allowed(lint) => {},
+            _ => self.warnings.push(warning),
+        }
+    }
+
+    fn use_name(&mut self, name: &str) {
+        if let Some(binding) = self.bindings.iter_mut().rev().find(|b| b.name == name) {
+            binding.used = true;
+        }
+    }
+
+    fn check_deprecated(&mut self, span: ByteSpan, name: &str) {
+        let binding = self.bindings.iter().rev().find(|b| b.name == name);
+        if let Some(&Some(ref message)) = binding.map(|b| &b.deprecated) {
+            self.warn(Warning::Deprecated {
+                span,
+                name: name.to_owned(),
+                message: message.clone(),
+            });
+        }
+    }
+
+    fn bind(&mut self, start: ByteIndex, name: &str, kind: BindingKind) {
+        let span = ByteSpan::from_offset(start, ByteOffset::from_str(name));
+
+        if !is_exempt(name) && kind != BindingKind::Label {
+            let original_span = self
+                .bindings
+                .iter()
+                .rev()
+                .find(|b| b.name == name)
+                .map(|b| b.span);
+
+            if let Some(original_span) = original_span {
+                self.warn(Warning::ShadowedName {
+                    span,
+                    original_span,
+                    name: name.to_owned(),
+                });
+            }
+        }
+
+        self.bindings.push(Binding {
+            name: name.to_owned(),
+            span,
+            kind,
+            used: false,
+            deprecated: None,
+            allowed: self.allowed.clone(),
+        });
+    }
+
+    /// Leave a scope, reporting any of the bindings introduced since `mark`
+    /// that were never used
+    fn exit_scope(&mut self, mark: usize) {
+        for binding in self.bindings.drain(mark..) {
+            if binding.used || is_exempt(&binding.name) {
+                continue;
+            }
+
+            let warning = match binding.kind {
+                BindingKind::Param => Warning::UnusedBinder {
+                    span: binding.span,
+                    name: binding.name,
+                },
+                BindingKind::Import(path) => Warning::UnusedImport {
+                    span: binding.span,
+                    name: binding.name,
+                    path,
+                },
+                BindingKind::Label | BindingKind::Other => continue,
+            };
+
+            match warning.lint() {
+                Some(lint) if binding.allowed.contains(&lint) => {},
+                _ => self.warnings.push(warning),
+            }
+        }
+    }
+
+    /// Check the attributes of a group of items, reporting any that are
+    /// unknown or malformed
+    fn check_attrs(&mut self, items: &[concrete::Item]) {
+        for attr in items.iter().flat_map(concrete::Item::attrs) {
+            let (_, ref attr_name) = attr.name;
+            let well_formed = match attr_name.as_str() {
+                "allow" => attr.value.is_none(),
+                "deprecated" => attr.args.is_empty(),
+                "inline" | "no_unfold" => attr.args.is_empty() && attr.value.is_none(),
+                _ => {
+                    self.warn(Warning::UnknownAttribute {
+                        span: attr.span,
+                        name: attr_name.clone(),
+                    });
+                    continue;
+                },
+            };
+
+            if !well_formed {
+                self.warn(Warning::MalformedAttribute {
+                    span: attr.span,
+                    name: attr_name.clone(),
+                });
+            }
+            if attr_name != "allow" {
+                continue;
+            }
+
+            for &(start, ref name) in &attr.args {
+                if Lint::from_name(name).is_none() {
+                    self.warn(Warning::UnknownLint {
+                        span: ByteSpan::from_offset(start, ByteOffset::from_str(name)),
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Allow the lints mentioned in the attributes of all the items in the
+    /// group with the given name, returning the previous state to restore
+    fn allow_attrs(&mut self, items: &[concrete::Item], name: &str) -> usize {
+        let mark = self.allowed.len();
+        let attrs = items
+            .iter()
+            .filter(|item| item.name() == Some(name))
+            .flat_map(concrete::Item::attrs)
+            .filter(|attr| attr.name.1 == "allow");
+
+        for attr in attrs {
+            let lints = attr
+                .args
+                .iter()
+                .filter_map(|(_, name)| Lint::from_name(name));
+            self.allowed.extend(lints);
+        }
+
+        mark
+    }
+
+    /// Mark the binding of the item with the given name as deprecated, if
+    /// any of the items in the group with that name have a `deprecated`
+    /// attribute
+    fn deprecate_attrs(&mut self, items: &[concrete::Item], group: usize, name: &str) {
+        let attr = items
+            .iter()
+            .filter(|item| item.name() == Some(name))
+            .flat_map(concrete::Item::attrs)
+            .find(|attr| attr.name.1 == "deprecated");
+
+        if let Some(attr) = attr {
+            let message = attr.value.as_ref().map(|&(_, ref message)| message.clone());
+            if let Some(binding) = self.bindings[group..].iter_mut().find(|b| b.name == name) {
+                binding.deprecated = Some(message);
+            }
+        }
+    }
+
+    fn items(&mut self, items: &[concrete::Item]) {
+        let group = self.bindings.len();
+        self.check_attrs(items);
+
+        // Bind the items up-front, because they may refer to the items that
+        // are defined after them. Items that shadow an existing binding are
+        // only bound after their definition, like in the desugarer.
+        let shadowing = (items.iter())
+            .filter_map(concrete::Item::name)
+            .filter(|&name| self.bindings[..group].iter().any(|b| b.name == name))
+            .collect::<Vec<_>>();
+
+        for item in items {
+            match item.name() {
+                Some(name) if !shadowing.contains(&name) => self.bind_item(items, group, item),
+                Some(_) | None => {},
+            }
+        }
+
+        for item in items {
+            match *item {
+                concrete::Item::Declaration {
+                    name: (_, ref name),
+                    ref ann,
+                    ..
+                } => {
+                    let mark = self.allow_attrs(items, name);
+                    self.term(ann);
+                    self.allowed.truncate(mark);
+                },
+                concrete::Item::Definition {
+                    name: (_, ref name),
+                    ref params,
+                    ref return_ann,
+                    ref body,
+                    ref where_items,
+                    ..
+                } => {
+                    let mark = self.allow_attrs(items, name);
+                    let return_ann = return_ann.as_ref().map(<_>::as_ref);
+                    self.fun_intro(params, return_ann, body, where_items);
+                    self.allowed.truncate(mark);
+
+                    if shadowing.contains(&name.as_str()) {
+                        self.bind_item(items, group, item);
+                    }
+                },
+                concrete::Item::Open { ref module, .. } => self.term(module),
+                concrete::Item::Error(_) => {},
+            }
+        }
+    }
+
+    /// Bind the name of an item in the group starting at `group`, unless
+    /// another item has already bound it
+    fn bind_item(&mut self, items: &[concrete::Item], group: usize, item: &concrete::Item) {
+        match *item {
+            concrete::Item::Declaration {
+                name: (start, ref name),
+                ..
+            } => {
+                let mark = self.allow_attrs(items, name);
+                if !self.bindings[group..].iter().any(|b| b.name == *name) {
+                    self.bind(start, name, BindingKind::Other);
+                    self.deprecate_attrs(items, group, name);
+                }
+                self.allowed.truncate(mark);
+            },
+            concrete::Item::Definition {
+                name: (start, ref name),
+                ref params,
+                ref body,
+                ..
+            } => {
+                let mark = self.allow_attrs(items, name);
+                let kind = match import_path(body) {
+                    Some(path) if params.is_empty() => BindingKind::Import(path.to_owned()),
+                    _ => BindingKind::Other,
+                };
+                match self.bindings[group..].iter().position(|b| b.name == *name) {
+                    Some(index) => self.bindings[group + index].kind = kind,
+                    None => {
+                        self.bind(start, name, kind);
+                        self.deprecate_attrs(items, group, name);
+                    },
+                }
+                self.allowed.truncate(mark);
+            },
+            concrete::Item::Open { .. } | concrete::Item::Error(_) => {},
+        }
+    }
+
+    fn fun_intro(
+        &mut self,
+        param_groups: &[concrete::FunIntroParamGroup],
+        return_ann: Option<&concrete::Term>,
+        body: &concrete::Term,
+        where_items: &[concrete::Item],
+    ) {
+        let mark = self.bindings.len();
+
+        for &(ref names, ref ann) in param_groups {
+            if let Some(ref ann) = *ann {
+                self.term(ann);
+            }
+            for &(start, ref name) in names {
+                self.bind(start, name, BindingKind::Param);
+            }
+        }
+
+        if let Some(return_ann) = return_ann {
+            self.term(return_ann);
+        }
+
+        let where_mark = self.bindings.len();
+        self.items(where_items);
+        self.term(body);
+        self.exit_scope(where_mark);
+
+        self.exit_scope(mark);
+    }
+
+    fn pattern(&mut self, pattern: &concrete::Pattern) {
+        match *pattern {
+            concrete::Pattern::Parens(_, ref pattern) => self.pattern(pattern),
+            concrete::Pattern::Ann(ref pattern, ref ty) => {
+                self.term(ty);
+                self.pattern(pattern);
+            },
+            concrete::Pattern::Name(span, ref name, shift) => {
+                // Names that are already in scope are matched by equality, so
+                // they count as uses rather than binders
+                if shift.is_some() || self.bindings.iter().any(|b| b.name == *name) {
+                    self.use_name(name);
+                } else {
+                    self.bind(span.start(), name, BindingKind::Other);
+                }
+            },
+            concrete::Pattern::Literal(_) | concrete::Pattern::Error(_) => {},
+        }
+    }
+
+    fn term(&mut self, term: &concrete::Term) {
+        use crate::syntax::concrete::{RecordIntroField, Term};
+
+        match *term {
+            Term::Parens(_, ref term) | Term::RecordProj(_, ref term, ..) => self.term(term),
+            Term::Ann(ref expr, ref ty) => {
+                self.term(expr);
+                self.term(ty);
+            },
+            Term::Universe(..)
+            | Term::Literal(_)
+            | Term::Hole(_)
+            | Term::Import(..)
+            | Term::Error(_) => {},
+            Term::ArrayIntro(_, ref elems) => {
+                for elem in elems {
+                    self.term(elem);
+                }
+            },
+            Term::Name(span, ref name, _) => {
+                self.use_name(name);
+                self.check_deprecated(span, name);
+            },
+            Term::FunType(_, ref params, ref body) => {
+                let mark = self.bindings.len();
+                for &(ref names, ref ann) in params {
+                    self.term(ann);
+                    for &(start, ref name) in names {
+                        self.bind(start, name, BindingKind::Other);
+                    }
+                }
+                self.term(body);
+                self.exit_scope(mark);
+            },
+            Term::FunIntro(_, ref params, ref body) => self.fun_intro(params, None, body, &[]),
+            Term::FunArrow(ref ann, ref body) => {
+                self.term(ann);
+                self.term(body);
+            },
+            Term::FunApp(ref head, ref args) => {
+                self.term(head);
+                for arg in args {
+                    self.term(arg);
+                }
+            },
+            Term::Let(_, ref items, ref body) | Term::Where(ref body, ref items, _) => {
+                let mark = self.bindings.len();
+                self.items(items);
+                self.term(body);
+                self.exit_scope(mark);
+            },
+            Term::If(_, ref cond, ref if_true, ref if_false) => {
+                self.term(cond);
+                self.term(if_true);
+                self.term(if_false);
+            },
+            Term::Case(_, ref head, ref arms) => {
+                self.term(head);
+                for &(ref pattern, ref body) in arms {
+                    let mark = self.bindings.len();
+                    self.pattern(pattern);
+                    self.term(body);
+                    self.exit_scope(mark);
+                }
+            },
+            Term::RecordType(_, ref fields) => {
+                let mark = self.bindings.len();
+                for field in fields {
+                    self.term(&field.ann);
+                    match field.binder {
+                        Some((start, ref name)) => self.bind(start, name, BindingKind::Other),
+                        None => {
+                            let (start, ref label) = field.label;
+                            self.bind(start, label, BindingKind::Label);
+                        },
+                    }
+                }
+                self.exit_scope(mark);
+            },
+            Term::RecordIntro(_, ref fields) => {
+                for field in fields {
+                    match *field {
+                        RecordIntroField::Punned {
+                            label: (start, ref name),
+                            ..
+                        } => {
+                            let span = ByteSpan::from_offset(start, ByteOffset::from_str(name));
+                            self.use_name(name);
+                            self.check_deprecated(span, name);
+                        },
+                        RecordIntroField::Explicit {
+                            ref params,
+                            ref return_ann,
+                            ref term,
+                            ..
+                        } => {
+                            let return_ann = return_ann.as_ref().map(<_>::as_ref);
+                            self.fun_intro(params, return_ann, term, &[]);
+                        },
+                    }
+                }
+            },
+        }
+    }
+}
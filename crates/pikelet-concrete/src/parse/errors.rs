@@ -5,6 +5,8 @@ use failure::Fail;
 use lalrpop_util::ParseError as LalrpopError;
 use std::fmt;
 
+use pikelet_core::limits::LimitExceeded;
+
 use crate::parse::{LexerError, Token};
 
 #[derive(Debug, Fail, Clone, PartialEq)]
@@ -34,6 +36,11 @@ pub enum ParseError {
         span: ByteSpan,
         token: Token<String>,
     },
+    #[fail(display = "Term too large: {}", limit)]
+    TermTooLarge {
+        span: ByteSpan,
+        limit: LimitExceeded,
+    },
 }
 
 /// Flatten away an LALRPOP error, leaving the inner `ParseError` behind
@@ -76,7 +83,8 @@ impl ParseError {
             ParseError::IdentifierExpectedInPiType { span }
             | ParseError::UnknownReplCommand { span, .. }
             | ParseError::UnexpectedToken { span, .. }
-            | ParseError::ExtraToken { span, .. } => span,
+            | ParseError::ExtraToken { span, .. }
+            | ParseError::TermTooLarge { span, .. } => span,
             ParseError::UnexpectedEof { end, .. } => ByteSpan::new(end, end),
         }
     }
@@ -111,6 +119,8 @@ impl ParseError {
                 Diagnostic::new_error(format!("extra token `{}`", token))
                     .with_label(Label::new_primary(span).with_message("extra token"))
             },
+            ParseError::TermTooLarge { span, limit } => Diagnostic::new_error("term too large")
+                .with_label(Label::new_primary(span).with_message(limit.to_string())),
         }
     }
 }
@@ -61,6 +61,8 @@ pub enum LexerError {
     EmptyCharLiteral { span: ByteSpan },
     #[fail(display = "An unknown escape code \\{} was found.", found)]
     UnknownEscapeCode { start: ByteIndex, found: char },
+    #[fail(display = "An invalid unicode escape code was found.")]
+    InvalidUnicodeEscape { span: ByteSpan },
     #[fail(
         display = "An integer literal {} was too large for the target type.",
         value
@@ -83,6 +85,7 @@ impl LexerError {
             | LexerError::UnterminatedOctLiteral { span }
             | LexerError::UnterminatedHexLiteral { span }
             | LexerError::EmptyCharLiteral { span }
+            | LexerError::InvalidUnicodeEscape { span }
             | LexerError::IntegerLiteralOverflow { span, .. } => span,
         }
     }
@@ -125,6 +128,12 @@ impl LexerError {
                 Diagnostic::new_error(format!("unknown escape code \\{}", found))
                     .with_label(Label::new_primary(char_span))
             },
+            LexerError::InvalidUnicodeEscape { span } => {
+                Diagnostic::new_error("invalid unicode escape code").with_label(
+                    Label::new_primary(span)
+                        .with_message("expected between 1 and 6 hex digits, like `\\u{1F600}`"),
+                )
+            },
             LexerError::IntegerLiteralOverflow { span, ref value } => {
                 Diagnostic::new_error(format!("integer literal overflow with value `{}`", value))
                     .with_label(Label::new_primary(span).with_message("overflowing literal"))
@@ -155,10 +164,12 @@ pub enum Token<S> {
     Import,     // import
     In,         // in
     Let,        // let
+    Open,       // open
     Record,     // record
     RecordType, // Record
     Then,       // then
     Type,       // Type
+    Using,      // using
     Where,      // where
 
     // Symbols
@@ -169,6 +180,7 @@ pub enum Token<S> {
     Dot,       // .
     DotDot,    // ..
     Equal,     // =
+    Hash,      // #
     LArrow,    // ->
     LFatArrow, // =>
     Question,  // ?
@@ -202,10 +214,12 @@ impl<S: fmt::Display> fmt::Display for Token<S> {
             Token::Import => write!(f, "import"),
             Token::In => write!(f, "in"),
             Token::Let => write!(f, "let"),
+            Token::Open => write!(f, "open"),
             Token::Record => write!(f, "record"),
             Token::RecordType => write!(f, "Record"),
             Token::Then => write!(f, "then"),
             Token::Type => write!(f, "Type"),
+            Token::Using => write!(f, "using"),
             Token::Where => write!(f, "where"),
             Token::BSlash => write!(f, "\\"),
             Token::Caret => write!(f, "^"),
@@ -214,6 +228,7 @@ impl<S: fmt::Display> fmt::Display for Token<S> {
             Token::Dot => write!(f, "."),
             Token::DotDot => write!(f, ".."),
             Token::Equal => write!(f, "="),
+            Token::Hash => write!(f, "#"),
             Token::LFatArrow => write!(f, "=>"),
             Token::LArrow => write!(f, "->"),
             Token::Question => write!(f, "?"),
@@ -247,10 +262,12 @@ impl<'input> From<Token<&'input str>> for Token<String> {
             Token::Import => Token::Import,
             Token::In => Token::In,
             Token::Let => Token::Let,
+            Token::Open => Token::Open,
             Token::Record => Token::Record,
             Token::RecordType => Token::RecordType,
             Token::Then => Token::Then,
             Token::Type => Token::Type,
+            Token::Using => Token::Using,
             Token::Where => Token::Where,
             Token::BSlash => Token::BSlash,
             Token::Caret => Token::Caret,
@@ -259,6 +276,7 @@ impl<'input> From<Token<&'input str>> for Token<String> {
             Token::Dot => Token::Dot,
             Token::DotDot => Token::DotDot,
             Token::Equal => Token::Equal,
+            Token::Hash => Token::Hash,
             Token::LFatArrow => Token::LFatArrow,
             Token::LArrow => Token::LArrow,
             Token::Question => Token::Question,
@@ -382,10 +400,12 @@ impl<'input> Lexer<'input> {
             "import" => Token::Import,
             "in" => Token::In,
             "let" => Token::Let,
+            "open" => Token::Open,
             "record" => Token::Record,
             "Record" => Token::RecordType,
             "then" => Token::Then,
             "Type" => Token::Type,
+            "using" => Token::Using,
             "where" => Token::Where,
             ident => Token::Ident(ident),
         };
@@ -403,12 +423,43 @@ impl<'input> Lexer<'input> {
             Some((_, 'n')) => Ok('\n'),
             Some((_, 'r')) => Ok('\r'),
             Some((_, 't')) => Ok('\t'),
-            // TODO: Unicode escape codes
+            Some((_, '0')) => Ok('\0'),
+            Some((_, 'u')) => self.unicode_escape(start),
             Some((start, ch)) => Err(LexerError::UnknownEscapeCode { start, found: ch }),
             None => Err(LexerError::UnexpectedEof { end: start }),
         }
     }
 
+    /// Consume the remainder of a unicode escape code, like `\u{1F600}`
+    fn unicode_escape(&mut self, start: ByteIndex) -> Result<char, LexerError> {
+        let digits_start = match self.bump() {
+            Some((next, '{')) => next + ByteOffset::from_char_utf8('{'),
+            Some((next, ch)) => {
+                return Err(LexerError::InvalidUnicodeEscape {
+                    span: ByteSpan::new(start, next + ByteOffset::from_char_utf8(ch)),
+                });
+            },
+            None => return Err(LexerError::UnexpectedEof { end: start }),
+        };
+
+        let (end, digits) = self.take_while(digits_start, is_hex_digit);
+        match self.bump() {
+            Some((next, '}')) => {
+                let span = ByteSpan::new(start, next + ByteOffset::from_char_utf8('}'));
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err(LexerError::InvalidUnicodeEscape { span });
+                }
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .ok_or(LexerError::InvalidUnicodeEscape { span })
+            },
+            Some(_) | None => Err(LexerError::InvalidUnicodeEscape {
+                span: ByteSpan::new(start, end),
+            }),
+        }
+    }
+
     /// Consume a string literal
     fn string_literal(&mut self, start: ByteIndex) -> Result<SpannedToken<'input>, LexerError> {
         let mut string = String::new();
@@ -428,6 +479,60 @@ impl<'input> Lexer<'input> {
         })
     }
 
+    /// Returns `true` if the lookahead is the start of a raw string literal,
+    /// assuming that an `r` has just been consumed
+    fn test_raw_string_start(&self) -> bool {
+        let mut chars = (self.lookahead.into_iter())
+            .chain(self.chars.clone())
+            .map(|(_, ch)| ch);
+
+        chars.find(|&ch| ch != '#') == Some('"')
+    }
+
+    /// Consume a raw string literal, like `r"C:\Users"` or `r#"say "hi""#`
+    ///
+    /// Escape codes are not interpreted in raw string literals, and the
+    /// literal is terminated by a quote followed by the same number of hashes
+    /// that it was opened with.
+    fn raw_string_literal(&mut self, start: ByteIndex) -> Result<SpannedToken<'input>, LexerError> {
+        let mut hashes = 0;
+        while self.test_lookahead(|ch| ch == '#') {
+            self.bump();
+            hashes += 1;
+        }
+        self.bump(); // skip '"'
+
+        let mut string = String::new();
+        let mut end = start;
+
+        while let Some((next, ch)) = self.bump() {
+            end = next + ByteOffset::from_char_utf8(ch);
+            if ch != '"' {
+                string.push(ch);
+                continue;
+            }
+
+            let mut closing = 0;
+            while closing < hashes && self.test_lookahead(|ch| ch == '#') {
+                self.bump();
+                closing += 1;
+            }
+            end += ByteOffset(closing as RawOffset);
+
+            if closing == hashes {
+                return Ok((start, Token::StringLiteral(string), end));
+            }
+            string.push('"');
+            for _ in 0..closing {
+                string.push('#');
+            }
+        }
+
+        Err(LexerError::UnterminatedStringLiteral {
+            span: ByteSpan::new(start, end),
+        })
+    }
+
     /// Consume a character literal
     fn char_literal(&mut self, start: ByteIndex) -> Result<SpannedToken<'input>, LexerError> {
         let ch = match self.bump() {
@@ -563,6 +668,7 @@ impl<'input> Iterator for Lexer<'input> {
                     }
                 },
                 '\\' => Ok((start, Token::BSlash, end)),
+                '#' => Ok((start, Token::Hash, end)),
                 '(' => Ok((start, Token::LParen, end)),
                 ')' => Ok((start, Token::RParen, end)),
                 '{' => Ok((start, Token::LBrace, end)),
@@ -571,6 +677,7 @@ impl<'input> Iterator for Lexer<'input> {
                 ']' => Ok((start, Token::RBracket, end)),
                 '"' => self.string_literal(start),
                 '\'' => self.char_literal(start),
+                'r' if self.test_raw_string_start() => self.raw_string_literal(start),
                 '0' if self.test_lookahead(|x| x == 'b') => self.bin_literal(start),
                 '0' if self.test_lookahead(|x| x == 'o') => self.oct_literal(start),
                 '0' if self.test_lookahead(|x| x == 'x') => self.hex_literal(start),
@@ -643,6 +750,32 @@ mod tests {
         };
     }
 
+    #[test]
+    fn string_literal_escapes() {
+        test! {
+            r#"  "\0\u{48}\u{1F600}"  "#,
+            r#"  ~~~~~~~~~~~~~~~~~~~  "# => Token::StringLiteral("\0H\u{1F600}".to_owned()),
+        };
+    }
+
+    #[test]
+    fn raw_string_literal() {
+        test! {
+            r###"  r"a\n" r#""b""#  r  "###,
+            r###"  ~~~~~~            "### => Token::StringLiteral("a\\n".to_owned()),
+            r###"         ~~~~~~~~   "### => Token::StringLiteral("\"b\"".to_owned()),
+            r###"                   ~  "### => Token::Ident("r"),
+        };
+    }
+
+    #[test]
+    fn raw_string_literal_multiline() {
+        test! {
+            "  r\"a\n  b\"  ",
+            "  ~~~~~~~~   " => Token::StringLiteral("a\n  b".to_owned()),
+        };
+    }
+
     #[test]
     fn char_literal() {
         test! {
@@ -695,36 +828,39 @@ mod tests {
     #[test]
     fn keywords() {
         test! {
-            "  as case else if import in let record Record then Type where  ",
-            "  ~~                                                              " => Token::As,
-            "     ~~~~                                                         " => Token::Case,
-            "          ~~~~                                                    " => Token::Else,
-            "               ~~                                                 " => Token::If,
-            "                  ~~~~~~                                          " => Token::Import,
-            "                         ~~                                       " => Token::In,
-            "                            ~~~                                   " => Token::Let,
-            "                                ~~~~~~                            " => Token::Record,
-            "                                       ~~~~~~                     " => Token::RecordType,
-            "                                              ~~~~                " => Token::Then,
-            "                                                   ~~~~           " => Token::Type,
-            "                                                        ~~~~~     " => Token::Where,
+            "  as case else if import in let open record Record then Type using where  ",
+            "  ~~                                                                         " => Token::As,
+            "     ~~~~                                                                    " => Token::Case,
+            "          ~~~~                                                               " => Token::Else,
+            "               ~~                                                            " => Token::If,
+            "                  ~~~~~~                                                     " => Token::Import,
+            "                         ~~                                                  " => Token::In,
+            "                            ~~~                                              " => Token::Let,
+            "                                ~~~~                                         " => Token::Open,
+            "                                     ~~~~~~                                  " => Token::Record,
+            "                                            ~~~~~~                           " => Token::RecordType,
+            "                                                   ~~~~                      " => Token::Then,
+            "                                                        ~~~~                 " => Token::Type,
+            "                                                             ~~~~~           " => Token::Using,
+            "                                                                   ~~~~~     " => Token::Where,
         };
     }
 
     #[test]
     fn symbols() {
         test! {
-            r" \ ^ : , .. = -> => ? ; ",
-            r" ~                      " => Token::BSlash,
-            r"   ~                    " => Token::Caret,
-            r"     ~                  " => Token::Colon,
-            r"       ~                " => Token::Comma,
-            r"         ~~             " => Token::DotDot,
-            r"            ~           " => Token::Equal,
-            r"              ~~        " => Token::LArrow,
-            r"                 ~~     " => Token::LFatArrow,
-            r"                    ~   " => Token::Question,
-            r"                      ~ " => Token::Semi,
+            r" \ ^ : , .. = # -> => ? ; ",
+            r" ~                        " => Token::BSlash,
+            r"   ~                      " => Token::Caret,
+            r"     ~                    " => Token::Colon,
+            r"       ~                  " => Token::Comma,
+            r"         ~~               " => Token::DotDot,
+            r"            ~             " => Token::Equal,
+            r"              ~           " => Token::Hash,
+            r"                ~~        " => Token::LArrow,
+            r"                   ~~     " => Token::LFatArrow,
+            r"                      ~   " => Token::Question,
+            r"                        ~ " => Token::Semi,
         }
     }
 
@@ -0,0 +1,124 @@
+//! Enforcing limits on the size of terms
+//!
+//! The parser works out the extent of each node as it is built, from the
+//! extents of its children, and checks it against the limits straight away.
+//! This way a syntax tree that exceeds the limits is never constructed, and
+//! later, recursive passes over the syntax can't overflow the stack on
+//! pathological input.
+//!
+//! Parameters, arguments, and items are desugared into terms of their own, so
+//! they are counted toward the size of a term. Each parameter binds a variable
+//! in a new scope, so parameters are counted toward the depth as well.
+//! Arguments and items are not, because they are traversed one after the
+//! other.
+
+use pikelet_core::limits::{LimitExceeded, Limits};
+
+use crate::syntax::concrete::{FunIntroParamGroup, FunIntroParams};
+
+/// How deeply the nodes of a syntax tree are nested, and how many of them
+/// there are
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Extent {
+    depth: usize,
+    size: usize,
+}
+
+impl Extent {
+    /// The extent of a node with no children
+    pub fn leaf() -> Extent {
+        Extent::default().parent()
+    }
+
+    /// The extent of this node, along with a node next to it
+    pub fn join(self, other: Extent) -> Extent {
+        Extent {
+            depth: usize::max(self.depth, other.depth),
+            size: self.size.saturating_add(other.size),
+        }
+    }
+
+    /// The extent of a node whose children have this extent
+    pub fn parent(self) -> Extent {
+        self.nest(1)
+    }
+
+    /// The extent of this node once it is nested inside some other nodes
+    pub fn nest(self, count: usize) -> Extent {
+        Extent {
+            depth: self.depth.saturating_add(count),
+            size: self.size.saturating_add(count),
+        }
+    }
+
+    /// Account for some arguments or items
+    pub fn count(self, count: usize) -> Extent {
+        Extent {
+            depth: self.depth,
+            size: self.size.saturating_add(count),
+        }
+    }
+
+    /// Check that the extent is within the given limits
+    pub fn check(self, limits: Limits) -> Result<(), LimitExceeded> {
+        if self.depth > limits.max_depth {
+            Err(LimitExceeded::Depth {
+                max_depth: limits.max_depth,
+            })
+        } else if self.size > limits.max_size {
+            Err(LimitExceeded::Size {
+                max_size: limits.max_size,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A node that has been parsed, along with its extent
+#[derive(Debug, Clone)]
+pub struct Measured<T> {
+    pub node: T,
+    pub extent: Extent,
+}
+
+impl<T> Measured<T> {
+    pub fn new(node: T, extent: Extent) -> Measured<T> {
+        Measured { node, extent }
+    }
+
+    pub fn leaf(node: T) -> Measured<T> {
+        Measured::new(node, Extent::leaf())
+    }
+
+    /// Split some nodes that are next to each other from their combined extent
+    pub fn unzip(nodes: Vec<Measured<T>>) -> (Vec<T>, Extent) {
+        let mut extent = Extent::default();
+        let nodes = (nodes.into_iter())
+            .map(|measured| {
+                extent = extent.join(measured.extent);
+                measured.node
+            })
+            .collect();
+
+        (nodes, extent)
+    }
+
+    /// The extent of an optional node
+    pub fn extent_of(node: &Option<Measured<T>>) -> Extent {
+        node.as_ref()
+            .map_or(Extent::default(), |measured| measured.extent)
+    }
+}
+
+/// Split some parameters from the extent of the body that they scope over
+pub fn unzip_params(
+    params: Vec<Measured<FunIntroParamGroup>>,
+    body: Extent,
+) -> (FunIntroParams, Extent) {
+    let extent = params.iter().rev().fold(body, |extent, param| {
+        param.extent.join(extent.nest(param.node.0.len()))
+    });
+
+    (params.into_iter().map(|param| param.node).collect(), extent)
+}
@@ -3,25 +3,37 @@
 use codespan::{ByteIndex, ByteSpan, FileMap};
 use lalrpop_util::ParseError as LalrpopError;
 
+use pikelet_core::limits::Limits;
+
 use crate::parse::lexer::Lexer;
+use crate::parse::limits::Measured;
 use crate::syntax::concrete;
 
 mod errors;
 mod lexer;
+mod limits;
 
 pub use self::errors::{ExpectedTokens, ParseError};
 pub use self::lexer::{LexerError, Token};
 
 macro_rules! parser {
-    ($name:ident, $output:ident, $parser_name:ident) => {
+    ($name:ident, $name_with_limits:ident, $output:ident, $parser_name:ident) => {
         pub fn $name<'input>(
             filemap: &'input FileMap,
+        ) -> (concrete::$output, Vec<String>, Vec<ParseError>) {
+            $name_with_limits(filemap, Limits::default())
+        }
+
+        pub fn $name_with_limits<'input>(
+            filemap: &'input FileMap,
+            limits: Limits,
         ) -> (concrete::$output, Vec<String>, Vec<ParseError>) {
             let mut import_paths = Vec::new();
             let mut errors = Vec::new();
             let lexer = Lexer::new(filemap).map(|x| x.map_err(ParseError::from));
             let value = grammar::$parser_name::new()
-                .parse(&mut import_paths, &mut errors, filemap, lexer)
+                .parse(&mut import_paths, &mut errors, filemap, limits, lexer)
+                .map(|value| value.node)
                 .unwrap_or_else(|err| {
                     errors.push(errors::from_lalrpop(filemap, err));
                     concrete::$output::Error(filemap.span())
@@ -32,8 +44,8 @@ macro_rules! parser {
     };
 }
 
-parser!(pattern, Pattern, PatternParser);
-parser!(term, Term, TermParser);
+parser!(pattern, pattern_with_limits, Pattern, PatternParser);
+parser!(term, term_with_limits, Term, TermParser);
 
 mod grammar {
     #![allow(clippy::all)]
@@ -41,6 +53,22 @@ mod grammar {
     include!(concat!(env!("OUT_DIR"), "/parse/grammar.rs"));
 }
 
+/// Check that a term or pattern that has just been parsed does not exceed the
+/// limits. This is done as each node is built, so that the parser never
+/// constructs a syntax tree that is too large to be traversed safely.
+fn check_limits<N, L, T>(
+    limits: Limits,
+    span: ByteSpan,
+    node: Measured<N>,
+) -> Result<Measured<N>, LalrpopError<L, T, ParseError>> {
+    match node.extent.check(limits) {
+        Ok(()) => Ok(node),
+        Err(limit) => Err(LalrpopError::User {
+            error: ParseError::TermTooLarge { span, limit },
+        }),
+    }
+}
+
 /// This is an ugly hack that cobbles together a pi type from a binder term and
 /// a body. See the comments on the `PiTerm` rule in the `grammar.lalrpop` for
 /// more information.
@@ -12,17 +12,26 @@ use crate::syntax::{concrete, FloatFormat, IntFormat};
 pub struct ResugarEnv {
     usages: im::HashMap<String, u32>,
     renames: im::HashMap<FreeVar<String>, String>,
+    /// Variables bound to opened records, whose fields are referred to by name
+    opened: im::HashSet<FreeVar<String>>,
 }
 
 const KEYWORDS: &[&str] = &[
-    "as", "case", "else", "if", "import", "in", "let", "record", "Record", "then", "Type", "where",
+    "as", "case", "else", "if", "import", "in", "let", "open", "record", "Record", "then", "Type",
+    "using", "where",
 ];
 
+/// The name of the variables that opened records are bound to during
+/// elaboration. This is a keyword, so it can't clash with any of the names in
+/// the source code.
+pub const OPENED_NAME: &str = "open";
+
 impl ResugarEnv {
     pub fn new() -> ResugarEnv {
         ResugarEnv {
             usages: KEYWORDS.iter().map(|&kw| (kw.to_owned(), 0)).collect(),
             renames: im::HashMap::new(),
+            opened: im::HashSet::new(),
         }
     }
 
@@ -348,6 +357,115 @@ fn resugar_fun_intro(
     )
 }
 
+/// Collect the labels of the fields that are projected out of the variable
+/// that an opened record is bound to, returning `false` if the variable is
+/// used in any other way
+fn opened_labels(term: &core::Term, free_var: &FreeVar<String>, labels: &mut Vec<Label>) -> bool {
+    use pikelet_core::syntax::core::Term;
+
+    match *term {
+        Term::RecordProj(ref expr, ref label, _) => match *expr.inner {
+            Term::Var(Var::Free(ref var), _) if var == free_var => {
+                if !labels.contains(label) {
+                    labels.push(label.clone());
+                }
+                true
+            },
+            _ => opened_labels(expr, free_var, labels),
+        },
+        Term::Var(Var::Free(ref var), _) => var != free_var,
+        Term::Var(Var::Bound(_), _) | Term::Universe(_) | Term::Literal(_) | Term::Import(_) => {
+            true
+        },
+        Term::Ann(ref term, ref ty) | Term::FunApp(ref term, ref ty) => {
+            opened_labels(term, free_var, labels) && opened_labels(ty, free_var, labels)
+        },
+        Term::FunType(ref scope) | Term::FunIntro(ref scope) => {
+            let (_, Embed(ref ann)) = scope.unsafe_pattern;
+            opened_labels(ann, free_var, labels)
+                && opened_labels(&scope.unsafe_body, free_var, labels)
+        },
+        Term::RecordType(ref scope) => (scope.unsafe_pattern.unsafe_patterns.iter())
+            .all(|&(_, _, Embed(ref ann))| opened_labels(ann, free_var, labels)),
+        Term::RecordIntro(ref fields) => {
+            (fields.iter()).all(|&(_, ref term)| opened_labels(term, free_var, labels))
+        },
+        Term::Case(ref head, ref clauses) => {
+            opened_labels(head, free_var, labels)
+                && clauses.iter().all(|scope| {
+                    opened_pattern_labels(&scope.unsafe_pattern, free_var, labels)
+                        && opened_labels(&scope.unsafe_body, free_var, labels)
+                })
+        },
+        Term::ArrayIntro(ref elems) => {
+            (elems.iter()).all(|elem| opened_labels(elem, free_var, labels))
+        },
+        Term::Let(ref scope) => {
+            (scope.unsafe_pattern.unsafe_patterns.iter())
+                .all(|&(_, Embed(ref term))| opened_labels(term, free_var, labels))
+                && opened_labels(&scope.unsafe_body, free_var, labels)
+        },
+    }
+}
+
+fn opened_pattern_labels(
+    pattern: &core::Pattern,
+    free_var: &FreeVar<String>,
+    labels: &mut Vec<Label>,
+) -> bool {
+    match *pattern {
+        core::Pattern::Ann(ref pattern, Embed(ref ty)) => {
+            opened_pattern_labels(pattern, free_var, labels) && opened_labels(ty, free_var, labels)
+        },
+        core::Pattern::Var(Embed(Var::Free(ref var)), _) => var != free_var,
+        core::Pattern::Var(Embed(Var::Bound(_)), _)
+        | core::Pattern::Binder(_)
+        | core::Pattern::Literal(_) => true,
+    }
+}
+
+/// Resugar the binding of an opened record back into an `open` item, so that
+/// the fields that are used from it are referred to by name
+///
+/// Returns `None` if the binding was not introduced by opening a record, or if
+/// one of the names would be captured by something else that is in scope.
+fn resugar_open(
+    env: &mut ResugarEnv,
+    binder: &Binder<String>,
+    module: &core::RcTerm,
+    body: &core::RcTerm,
+) -> Option<concrete::Item> {
+    let Binder(ref free_var) = *binder;
+    match free_var.pretty_name {
+        Some(ref name) if name == OPENED_NAME => {},
+        Some(_) | None => return None,
+    }
+
+    let mut labels = Vec::new();
+    if !opened_labels(body, free_var, &mut labels) {
+        return None;
+    }
+    if (labels.iter()).any(|&Label(ref name)| env.usages.contains_key(name)) {
+        return None;
+    }
+
+    let module = resugar_term(env, module, Prec::LAM);
+    for &Label(ref name) in &labels {
+        env.usages.insert(name.clone(), 0);
+    }
+    env.opened.insert(free_var.clone());
+
+    Some(concrete::Item::Open {
+        span: ByteSpan::default(),
+        module,
+        using: Some(
+            (labels.into_iter())
+                .map(|Label(name)| (ByteIndex::default(), name))
+                .collect(),
+        ),
+    })
+}
+
 fn resugar_let(
     env: &ResugarEnv,
     scope: &Scope<Nest<(Binder<String>, Embed<core::RcTerm>)>, core::RcTerm>,
@@ -361,6 +479,12 @@ fn resugar_let(
     let mut items = Vec::with_capacity(bindings.len() * 2);
 
     for (binder, Embed(term)) in bindings {
+        // Opened records are always bound on their own by the elaborator
+        if let Some(item) = resugar_open(&mut env, &binder, &term, &body) {
+            items.push(item);
+            continue;
+        }
+
         let name = env.on_binder(&binder);
 
         match *term.inner {
@@ -372,14 +496,17 @@ fn resugar_let(
                 };
 
                 items.push(concrete::Item::Declaration {
+                    attrs: vec![],
                     name: (ByteIndex::default(), name.clone()),
                     ann: resugar_term(&env, &ann, Prec::ANN),
                 });
                 items.push(concrete::Item::Definition {
+                    attrs: vec![],
                     name: (ByteIndex::default(), name),
                     params: term_params,
                     return_ann: None,
                     body: term_body,
+                    where_items: vec![],
                 });
             },
             _ => {
@@ -390,10 +517,12 @@ fn resugar_let(
                 };
 
                 items.push(concrete::Item::Definition {
+                    attrs: vec![],
                     name: (ByteIndex::default(), name),
                     params: term_params,
                     return_ann: None,
                     body: term_body,
+                    where_items: vec![],
                 });
             },
         }
@@ -403,6 +532,11 @@ fn resugar_let(
         let (bindings, next_body) = scope.clone().unbind();
 
         for (binder, Embed(term)) in bindings.unnest() {
+            if let Some(item) = resugar_open(&mut env, &binder, &term, &next_body) {
+                items.push(item);
+                continue;
+            }
+
             let next_name = env.on_binder(&binder);
             match *term.inner {
                 core::Term::Ann(ref term, ref ann) => {
@@ -413,14 +547,17 @@ fn resugar_let(
                     };
 
                     items.push(concrete::Item::Declaration {
+                        attrs: vec![],
                         name: (ByteIndex::default(), next_name.clone()),
                         ann: resugar_term(&env, &ann, Prec::ANN),
                     });
                     items.push(concrete::Item::Definition {
+                        attrs: vec![],
                         name: (ByteIndex::default(), next_name),
                         params: term_params,
                         return_ann: None,
                         body: term_body,
+                        where_items: vec![],
                     });
                 },
                 _ => {
@@ -431,10 +568,12 @@ fn resugar_let(
                     };
 
                     items.push(concrete::Item::Definition {
+                        attrs: vec![],
                         name: (ByteIndex::default(), next_name),
                         params: term_params,
                         return_ann: None,
                         body: term_body,
+                        where_items: vec![],
                     });
                 },
             }
@@ -583,13 +722,21 @@ fn resugar_term(env: &ResugarEnv, term: &core::Term, prec: Prec) -> concrete::Te
                 LevelShift(shift) => Some(shift),
             };
 
-            concrete::Term::RecordProj(
-                ByteSpan::default(),
-                Box::new(resugar_term(env, expr, Prec::ATOMIC)),
-                ByteIndex::default(),
-                label.clone(),
-                shift,
-            )
+            match *expr.inner {
+                // Fields of opened records are referred to by name
+                core::Term::Var(Var::Free(ref free_var), LevelShift(0))
+                    if env.opened.contains(free_var) =>
+                {
+                    concrete::Term::Name(ByteSpan::default(), label.clone(), shift)
+                },
+                _ => concrete::Term::RecordProj(
+                    ByteSpan::default(),
+                    Box::new(resugar_term(env, expr, Prec::ATOMIC)),
+                    ByteIndex::default(),
+                    label.clone(),
+                    shift,
+                ),
+            }
         },
         // TODO: Resugar boolean patterns into if-then-else expressions?
         core::Term::Case(ref head, ref clauses) => concrete::Term::Case(
@@ -39,6 +39,43 @@ pub enum RecordIntroField {
     },
 }
 
+/// An attribute attached to an item
+///
+/// ```text
+/// #[allow(unused-binders, shadowing)]
+/// #[deprecated "use `bar` instead"]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub span: ByteSpan,
+    pub name: (ByteIndex, String),
+    pub args: Vec<(ByteIndex, String)>,
+    /// A string given after the arguments
+    pub value: Option<(ByteSpan, String)>,
+}
+
+impl Attribute {
+    pub fn to_doc(&self) -> Doc<BoxDoc<()>> {
+        let (_, ref name) = self.name;
+
+        Doc::text("#[")
+            .append(Doc::as_string(name))
+            .append(match self.args[..] {
+                [] => Doc::nil(),
+                _ => Doc::text("(")
+                    .append(Doc::intersperse(
+                        self.args.iter().map(|&(_, ref arg)| Doc::as_string(arg)),
+                        Doc::text(",").append(Doc::space()),
+                    ))
+                    .append(")"),
+            })
+            .append(self.value.as_ref().map_or(Doc::nil(), |&(_, ref value)| {
+                Doc::space().append(format!("{:?}", value))
+            }))
+            .append("]")
+    }
+}
+
 /// Top-level items within a module
 #[derive(Debug, Clone, PartialEq)]
 pub enum Item {
@@ -48,6 +85,7 @@ pub enum Item {
     /// foo : some-type
     /// ```
     Declaration {
+        attrs: Vec<Attribute>,
         name: (ByteIndex, String),
         ann: Term,
     },
@@ -56,12 +94,28 @@ pub enum Item {
     /// ```text
     /// foo = some-body
     /// foo x (y : some-type) = some-body
+    /// foo x = some-body where { helper = some-term; }
     /// ```
     Definition {
+        attrs: Vec<Attribute>,
         name: (ByteIndex, String),
         params: FunIntroParams,
         return_ann: Option<Box<Term>>,
         body: Term,
+        /// Auxiliary items that are only in scope in the body of this definition
+        where_items: Vec<Item>,
+    },
+    /// Brings the fields of a record into scope, optionally restricted to
+    /// the listed labels
+    ///
+    /// ```text
+    /// open prelude;
+    /// open prelude using (id, const);
+    /// ```
+    Open {
+        span: ByteSpan,
+        module: Term,
+        using: Option<Vec<(ByteIndex, String)>>,
     },
     /// Items that could not be correctly parsed
     ///
@@ -70,24 +124,52 @@ pub enum Item {
 }
 
 impl Item {
+    /// The name that this item declares or defines
+    pub fn name(&self) -> Option<&str> {
+        match *self {
+            Item::Declaration {
+                name: (_, ref name),
+                ..
+            }
+            | Item::Definition {
+                name: (_, ref name),
+                ..
+            } => Some(name),
+            Item::Open { .. } | Item::Error(_) => None,
+        }
+    }
+
+    /// The attributes attached to this item
+    pub fn attrs(&self) -> &[Attribute] {
+        match *self {
+            Item::Declaration { ref attrs, .. } | Item::Definition { ref attrs, .. } => attrs,
+            Item::Open { .. } | Item::Error(_) => &[],
+        }
+    }
+
     /// Return the span of source code that this declaration originated from
     pub fn span(&self) -> ByteSpan {
         match *self {
             Item::Definition {
                 name: (start, _),
-                body: ref term,
+                ref body,
+                ref where_items,
                 ..
-            }
-            | Item::Declaration {
+            } => match where_items.last() {
+                None => ByteSpan::new(start, body.span().end()),
+                Some(item) => ByteSpan::new(start, item.span().end()),
+            },
+            Item::Declaration {
                 name: (start, _),
                 ann: ref term,
+                ..
             } => ByteSpan::new(start, term.span().end()),
-            Item::Error(span) => span,
+            Item::Open { span, .. } | Item::Error(span) => span,
         }
     }
 
     pub fn to_doc(&self) -> Doc<BoxDoc<()>> {
-        match *self {
+        let doc = match *self {
             Item::Declaration {
                 name: (_, ref name),
                 ref ann,
@@ -102,6 +184,8 @@ impl Item {
                 ref params,
                 ref return_ann,
                 ref body,
+                ref where_items,
+                ..
             } => Doc::as_string(name)
                 .append(Doc::space())
                 .append(match params[..] {
@@ -115,10 +199,36 @@ impl Item {
                 }))
                 .append("=")
                 .append(Doc::space())
-                .append(body.to_doc().nest(PRETTY_INDENT_WIDTH)),
+                .append(body.to_doc().nest(PRETTY_INDENT_WIDTH))
+                .append(match where_items[..] {
+                    [] => Doc::nil(),
+                    _ => pretty_where_items(where_items),
+                }),
+            Item::Open {
+                ref module,
+                ref using,
+                ..
+            } => Doc::text("open")
+                .append(Doc::space())
+                .append(module.to_doc())
+                .append(using.as_ref().map_or(Doc::nil(), |using| {
+                    Doc::space()
+                        .append("using")
+                        .append(Doc::space())
+                        .append("(")
+                        .append(Doc::intersperse(
+                            using.iter().map(|&(_, ref name)| Doc::as_string(name)),
+                            Doc::text(",").append(Doc::space()),
+                        ))
+                        .append(")")
+                })),
             Item::Error(_) => Doc::text("<error>"),
         }
-        .append(";")
+        .append(";");
+
+        self.attrs().iter().rev().fold(doc, |acc, attr| {
+            attr.to_doc().append(Doc::newline()).append(acc)
+        })
     }
 }
 
@@ -442,21 +552,14 @@ impl Term {
                         items.iter().map(|item| item.to_doc()),
                         Doc::newline(),
                     ))
+                    .append(Doc::space())
                     .append("in")
+                    .append(Doc::space())
                     .append(body.to_doc())
             },
             Term::Where(ref expr, ref items, _) => Doc::nil()
                 .append(expr.to_doc())
-                .append(Doc::newline())
-                .append("where {")
-                .append(Doc::newline())
-                .append(Doc::intersperse(
-                    items.iter().map(|item| item.to_doc().group()),
-                    Doc::newline(),
-                ))
-                .append(Doc::newline())
-                .nest(PRETTY_INDENT_WIDTH)
-                .append("}"),
+                .append(pretty_where_items(items)),
             Term::If(_, ref cond, ref if_true, ref if_false) => Doc::nil()
                 .append("if")
                 .append(Doc::space())
@@ -579,6 +682,20 @@ impl fmt::Display for Term {
     }
 }
 
+fn pretty_where_items(items: &[Item]) -> Doc<BoxDoc<()>> {
+    Doc::nil()
+        .append(Doc::newline())
+        .append("where {")
+        .append(Doc::newline())
+        .append(Doc::intersperse(
+            items.iter().map(|item| item.to_doc().group()),
+            Doc::newline(),
+        ))
+        .append(Doc::newline())
+        .nest(PRETTY_INDENT_WIDTH)
+        .append("}")
+}
+
 fn pretty_fun_intro_params(params: &[FunIntroParamGroup]) -> Doc<BoxDoc<()>> {
     Doc::intersperse(
         params.iter().map(|&(ref names, ref ann)| match *ann {
@@ -73,3 +73,28 @@ impl<N: Clone + PartialEq> BoundPattern<N> for FloatFormat {
     fn visit_binders(&self, _: &mut impl FnMut(&Binder<N>)) {}
     fn visit_mut_binders(&mut self, _: &mut impl FnMut(&mut Binder<N>)) {}
 }
+
+/// How a definition should be treated when checking the items that follow it,
+/// as controlled by the `inline` and `no_unfold` attributes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unfold {
+    /// Unfold the definition when needed, keeping it bound in the elaborated term
+    Default,
+    /// Unfold the definition when needed, substituting it into the elaborated term
+    Inline,
+    /// Never unfold the definition, treating it as opaque when comparing types
+    Never,
+}
+
+impl<N: Clone + PartialEq> BoundPattern<N> for Unfold {
+    fn pattern_eq(&self, other: &Unfold) -> bool {
+        self == other
+    }
+
+    fn close_pattern(&mut self, _: ScopeState, _: &impl OnFreeFn<N>) {}
+    fn open_pattern(&mut self, _: ScopeState, _: &impl OnBoundFn<N>) {}
+    fn visit_vars(&self, _: &mut impl FnMut(&Var<N>)) {}
+    fn visit_mut_vars(&mut self, _: &mut impl FnMut(&mut Var<N>)) {}
+    fn visit_binders(&self, _: &mut impl FnMut(&Binder<N>)) {}
+    fn visit_mut_binders(&mut self, _: &mut impl FnMut(&mut Binder<N>)) {}
+}
@@ -10,7 +10,7 @@ use std::rc::Rc;
 
 use pikelet_core::syntax::{Label, Level, LevelShift};
 
-use crate::syntax::{FloatFormat, IntFormat, PRETTY_FALLBACK_WIDTH};
+use crate::syntax::{FloatFormat, IntFormat, Unfold, PRETTY_FALLBACK_WIDTH};
 
 /// Literals
 #[derive(Debug, Clone, PartialEq, PartialOrd, BoundTerm, BoundPattern)]
@@ -169,8 +169,11 @@ pub enum Term {
     /// Let bindings
     Let(
         ByteSpan,
-        Scope<Nest<(Binder<String>, Embed<RcTerm>)>, RcTerm>,
+        Scope<Nest<(Binder<String>, Unfold, Embed<RcTerm>)>, RcTerm>,
     ),
+    /// Bring the fields of a record into scope in the body, optionally
+    /// restricted to the listed labels
+    Open(ByteSpan, RcTerm, Option<Vec<(ByteSpan, Label)>>, RcTerm),
 }
 
 impl Term {
@@ -187,7 +190,8 @@ impl Term {
             | Term::RecordProj(span, ..)
             | Term::Case(span, ..)
             | Term::ArrayIntro(span, ..)
-            | Term::Let(span, ..) => span,
+            | Term::Let(span, ..)
+            | Term::Open(span, ..) => span,
             Term::Literal(ref literal) => literal.span(),
             Term::Ann(ref expr, ref ty) => expr.span().to(ty.span()),
             Term::FunApp(ref head, ref arg) => head.span().to(arg.span()),
@@ -249,8 +253,13 @@ impl Term {
                 .append(Doc::space())
                 .append(Doc::intersperse(
                     scope.unsafe_pattern.unsafe_patterns.iter().map(
-                        |&(ref binder, Embed(ref term))| {
+                        |&(ref binder, unfold, Embed(ref term))| {
                             Doc::nil()
+                                .append(match unfold {
+                                    Unfold::Default => Doc::nil(),
+                                    Unfold::Inline => Doc::text("#[inline]").append(Doc::space()),
+                                    Unfold::Never => Doc::text("#[no_unfold]").append(Doc::space()),
+                                })
                                 .append(Doc::as_string(binder))
                                 .append(Doc::space())
                                 .append("=")
@@ -264,6 +273,25 @@ impl Term {
                 .append("in")
                 .append(Doc::space())
                 .append(scope.unsafe_body.to_doc_expr()),
+            Term::Open(_, ref module, ref using, ref body) => Doc::nil()
+                .append("open")
+                .append(Doc::space())
+                .append(module.to_doc())
+                .append(using.as_ref().map_or(Doc::nil(), |using| {
+                    Doc::space()
+                        .append("using")
+                        .append(Doc::space())
+                        .append("(")
+                        .append(Doc::intersperse(
+                            using.iter().map(|&(_, ref label)| Doc::as_string(label)),
+                            Doc::text(",").append(Doc::space()),
+                        ))
+                        .append(")")
+                }))
+                .append(Doc::space())
+                .append("in")
+                .append(Doc::space())
+                .append(body.to_doc_expr()),
             ref term => term.to_doc_arrow(),
         }
     }
@@ -299,6 +327,7 @@ impl Term {
     fn to_doc_atomic(&self) -> Doc<BoxDoc<()>> {
         match *self {
             Term::Universe(_, level) => Doc::text(format!("Type^{}", level)),
+            Term::Literal(ref literal) => literal.to_doc(),
             Term::ArrayIntro(_, ref elems) => Doc::nil()
                 .append("[")
                 .append(Doc::intersperse(
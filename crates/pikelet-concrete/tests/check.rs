@@ -213,3 +213,74 @@ fn array_intro_elem_ty_mismatch() {
         Ok(term) => panic!("expected error but found: {}", term),
     }
 }
+
+#[test]
+fn trace_failure() {
+    use std::rc::Rc;
+
+    use pikelet_concrete::elaborate::{Trace, TraceStep};
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let expected_ty = r"Record { x : String }";
+    let given_expr = r#"record { x = 1 }"#;
+
+    let expected_ty = support::parse_nf_term(&mut codemap, &context, expected_ty);
+    let trace = Rc::new(Trace::new(1));
+    context.set_trace(Some(trace.clone()));
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::check_term(&context, &raw_term, &expected_ty) {
+        Err(_) => {},
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+
+    // Only the outermost step should be recorded
+    match trace.take_steps().as_slice() {
+        [TraceStep::Enter {
+            depth: 0,
+            rule: "C-RECORD",
+            locals: 0,
+            ..
+        }, TraceStep::Failed { depth: 0, .. }] => {},
+        steps => panic!("unexpected trace: {:?}", steps),
+    }
+}
+
+#[test]
+fn trace_locals() {
+    use std::rc::Rc;
+
+    use pikelet_concrete::elaborate::{Trace, TraceStep};
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let expected_ty = r"String -> String -> String";
+    let given_expr = r"\x y => x";
+
+    let expected_ty = support::parse_nf_term(&mut codemap, &context, expected_ty);
+    let trace = Rc::new(Trace::new(32));
+    context.set_trace(Some(trace.clone()));
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    elaborate::check_term(&context, &raw_term, &expected_ty).unwrap();
+
+    // Each lambda binds another local for the steps nested inside it
+    let locals = trace
+        .take_steps()
+        .iter()
+        .map(|step| match *step {
+            TraceStep::Enter { depth, locals, .. } => (depth, locals),
+            TraceStep::Failed { .. } => panic!("unexpected failure: {}", step),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(locals, [(0, 0), (1, 1), (2, 2), (3, 2)]);
+}
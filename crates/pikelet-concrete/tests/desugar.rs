@@ -1,7 +1,7 @@
 use codespan::{ByteSpan, CodeMap, FileName};
 use codespan_reporting::termcolor::{ColorChoice, StandardStream};
 use goldenfile::Mint;
-use moniker::{assert_term_eq, Binder, Embed, FreeVar, Scope, Var};
+use moniker::{assert_term_eq, Binder, Embed, FreeVar, Nest, Scope, Var};
 use pretty_assertions::assert_eq;
 use std::io::Write;
 
@@ -353,27 +353,98 @@ fn let_forward_declarations() {
     }
 }
 
-// #[test]
-// fn let_forward_declarations_forward_ref() {
-//     let mut codemap = CodeMap::new();
-//     let desugar_env = DesugarEnv::new(im::HashMap::new());
-
-//     let src = "
-//         let
-//             foo : Type;
-//             bar : Type;
-//             bar = foo;
-//             foo = Record {};
-//         in
-//             record {}
-//     ";
-
-//     match parse_term(&mut codemap, src).desugar(&desugar_env) {
-//         Ok(_) => panic!("expected error"),
-//         Err(DesugarError::UndefinedName { .. }) => {},
-//         Err(err) => panic!("unexpected error: {}", err),
-//     }
-// }
+#[test]
+fn let_forward_declarations_forward_ref() {
+    let mut codemap = CodeMap::new();
+    let desugar_env = DesugarEnv::new(im::HashMap::new());
+
+    let src = "
+        let
+            foo : Type;
+            bar : Type;
+            bar = foo;
+            foo = Record {};
+        in
+            record {}
+    ";
+
+    let raw_term = parse_term(&mut codemap, src).desugar(&desugar_env).unwrap();
+
+    match *raw_term.inner {
+        raw::Term::Let(_, ref scope) => {
+            let names = (scope.unsafe_pattern.unsafe_patterns.iter())
+                .map(|&(ref binder, _, _)| binder.0.pretty_name.clone().unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(names, ["foo", "bar"]);
+        },
+        ref term => panic!("unexpected term: {}", term),
+    }
+}
+
+#[test]
+fn let_recursive_definitions() {
+    let mut codemap = CodeMap::new();
+    let desugar_env = DesugarEnv::new(im::HashMap::new());
+
+    let src = "
+        let
+            foo = bar;
+            bar = baz;
+            baz = foo;
+        in
+            record {}
+    ";
+
+    match parse_term(&mut codemap, src).desugar(&desugar_env) {
+        Ok(_) => panic!("expected error"),
+        Err(DesugarError::RecursiveDefinitions { name, via, .. }) => {
+            assert_eq!(name, "foo");
+            assert_eq!(via.len(), 2);
+        },
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn let_open() {
+    let mut codemap = CodeMap::new();
+    let desugar_env = DesugarEnv::new(im::HashMap::new());
+
+    let src = "
+        let
+            open foo using (bar);
+            baz = bar;
+            foo = record { bar = Record {} };
+        in
+            record {}
+    ";
+
+    let raw_term = parse_term(&mut codemap, src).desugar(&desugar_env).unwrap();
+    let names = |scope: &Scope<Nest<(Binder<String>, _, _)>, _>| {
+        (scope.unsafe_pattern.unsafe_patterns.iter())
+            .map(|&(ref binder, _, _)| binder.0.pretty_name.clone().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // The open depends on `foo`, but `baz` depends on the open
+    match *raw_term.inner {
+        raw::Term::Let(_, ref scope) => {
+            assert_eq!(names(scope), ["foo"]);
+            match *scope.unsafe_body.inner {
+                raw::Term::Open(_, _, Some(ref using), ref body) => {
+                    assert_eq!(using.len(), 1);
+                    match *body.inner {
+                        raw::Term::Let(_, ref scope) => assert_eq!(names(scope), ["baz"]),
+                        ref term => panic!("unexpected term: {}", term),
+                    }
+                },
+                ref term => panic!("unexpected term: {}", term),
+            }
+        },
+        ref term => panic!("unexpected term: {}", term),
+    }
+}
 
 #[test]
 fn let_declaration_after_definition() {
@@ -516,4 +587,14 @@ mod sugar {
             parse_desugar_term(&env, r#"record { x = x; y = y }"#),
         )
     }
+
+    #[test]
+    fn definition_where() {
+        let env = DesugarEnv::new(im::HashMap::new());
+
+        assert_term_eq!(
+            parse_desugar_term(&env, r"let f (a : Type) (x : a) = y where { y = x; }; in f"),
+            parse_desugar_term(&env, r"let f (a : Type) (x : a) = let y = x; in y; in f"),
+        )
+    }
 }
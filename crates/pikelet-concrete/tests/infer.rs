@@ -274,6 +274,26 @@ fn id_fun_app_arr_fun_ty() {
     );
 }
 
+#[test]
+fn fun_app_many_args() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    // The spine of an application is elaborated in a loop, so we get an error
+    // here, rather than overflowing the stack
+    let given_expr = format!(r"(\x : Type => x){}", " String".repeat(10_000));
+    let raw_term = support::parse_term(&mut codemap, &given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::ArgAppliedToNonFunction { .. }) => {},
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok((term, _)) => panic!("expected error, found: {}", term),
+    }
+}
+
 #[test]
 fn apply() {
     let mut codemap = CodeMap::new();
@@ -381,6 +401,312 @@ fn let_expr_2() {
     );
 }
 
+#[test]
+fn let_forward_ref() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"
+        let
+            greeting : Name;
+            greeting = "hello";
+
+            Name = String;
+        in
+            greeting
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_forward_ref_shadowed() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    // Items before a shadowing definition still refer to the outer binding
+    let expected_ty = r"Record { test : Type }";
+    let given_expr = r#"
+        record {
+            test = (\x : Type =>
+                let
+                    y : Type = x;
+                    x : S32;
+                    x = 2;
+                in
+                    y
+            ) String;
+        }
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_where() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"
+        let
+            f (a : Type) (x : a) : a = y where {
+                y : a;
+                y = x;
+            };
+        in
+            f String "hello"
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_where_forward_ref() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"Record { greeting : String }";
+    let given_expr = r#"
+        let
+            greetings = record { greeting } where {
+                greeting : Name;
+                greeting = "hello";
+
+                Name = String;
+            };
+        in
+            greetings
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_where_out_of_scope() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            greeting = hello where { hello = "hello"; };
+        in
+            hello
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::UndefinedName { ref free_var, .. }) => {
+            assert_eq!(free_var.pretty_name, Some("hello".to_owned()));
+        },
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
+#[test]
+fn let_open() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"
+        let
+            greetings = record { Name = String; hello = "hello" };
+            open greetings;
+
+            greeting : Name;
+            greeting = hello;
+        in
+            greeting
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_open_shared() {
+    use pikelet_core::syntax::core::Term;
+
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let given_expr = r#"
+        let
+            open record { hello = "hello"; goodbye = "goodbye" };
+        in
+            record { a = hello; b = goodbye; c = hello }
+    "#;
+    let expected_expr = r#"record { a = "hello"; b = "goodbye"; c = "hello" }"#;
+
+    // The opened record should only be elaborated once, rather than being
+    // copied into each of the projections out of it
+    let (term, _) = support::parse_infer_term(&mut codemap, &context, given_expr);
+    match *term.inner {
+        Term::Let(ref scope) => {
+            let (bindings, body) = scope.clone().unbind();
+            assert_eq!(bindings.unsafe_patterns.len(), 1);
+            match *body.inner {
+                Term::RecordIntro(ref fields) => {
+                    for field in fields {
+                        match *field.1.inner {
+                            Term::RecordProj(ref expr, _, _) => match *expr.inner {
+                                Term::Var(_, _) => {},
+                                _ => panic!("expected a projection from a variable"),
+                            },
+                            _ => panic!("expected a projection"),
+                        }
+                    }
+                },
+                _ => panic!("expected a record"),
+            }
+        },
+        _ => panic!("expected the opened record to be bound with a let"),
+    }
+
+    assert_term_eq!(
+        support::parse_nf_term(&mut codemap, &context, given_expr),
+        support::parse_nf_term(&mut codemap, &context, expected_expr),
+    );
+}
+
+#[test]
+fn let_open_using() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            open record { hello = "hello"; goodbye = "goodbye" } using (hello);
+        in
+            goodbye
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::UndefinedName { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
+#[test]
+fn let_open_using_missing_field() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            open record { hello = "hello" } using (goodbye);
+        in
+            hello
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::NoFieldInType { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
+#[test]
+fn let_open_ambiguous() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            open record { hello = "hello"; one = 1 : S32 };
+            open record { hello = "bonjour" };
+        in
+            hello
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::AmbiguousName { ref opens, .. }) if opens.len() == 2 => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
+#[test]
+fn let_open_ambiguous_unused() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"S32";
+    let given_expr = r#"
+        let
+            open record { hello = "hello"; one = 1 : S32 };
+            open record { hello = "bonjour" };
+        in
+            one
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_open_non_record() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            open "hello";
+        in
+            hello
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::OpenedNonRecord { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
 #[test]
 fn let_shift_universes() {
     let mut codemap = CodeMap::new();
@@ -532,6 +858,126 @@ fn let_shift_universes_too_much() {
     support::parse_infer_term(&mut codemap, &context, given_expr);
 }
 
+#[test]
+fn term_too_deep() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+    context.set_limits(Limits {
+        max_eval_depth: 4,
+        ..Limits::default()
+    });
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let id = \(a : Type) => a;
+        in
+            ("hello" : id (id (id (id String))))
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    // The error should point at the annotated term
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::TermTooLarge {
+            span: Some(span),
+            limit: LimitExceeded::EvalDepth { max_eval_depth: 4 },
+        }) => {
+            let expected_start = given_expr.find(r#""hello""#).unwrap();
+            assert_eq!(span.start().to_usize() - 1, expected_start);
+        },
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+}
+
+#[test]
+fn int_literal_default() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"S32";
+    let given_expr = r"1";
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn float_literal_default() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"F64";
+    let given_expr = r"1.5";
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn int_literal_default_large() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"S64";
+    let given_expr = r"3000000000";
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn int_literal_default_too_large() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r"10000000000000000000";
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Err(TypeError::AmbiguousIntLiteral { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {:?}", term, ty),
+    }
+
+    // An annotation is enough to resolve the ambiguity
+    let expected_ty = r"U64";
+    let given_expr = r"10000000000000000000 : U64";
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn int_literal_expected() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"U8";
+    let given_expr = r"(\(x : U8) => x) 1";
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
 #[test]
 fn case_expr() {
     let mut codemap = CodeMap::new();
@@ -878,6 +1324,74 @@ fn record_proj_shift() {
     );
 }
 
+#[test]
+fn let_no_unfold() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+    let desugar_env = DesugarEnv::new(context.mappings());
+
+    let given_expr = r#"
+        let
+            #[no_unfold]
+            Name = String;
+
+            name : Name = "hello";
+        in
+            record {}
+    "#;
+
+    let raw_term = support::parse_term(&mut codemap, given_expr)
+        .desugar(&desugar_env)
+        .unwrap();
+
+    match elaborate::infer_term(&context, &raw_term) {
+        Ok(_) => panic!("expected error"),
+        Err(TypeError::LiteralMismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn let_no_unfold_escaping_ty() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let expected_ty = r"String -> String";
+    let given_expr = r#"
+        let
+            #[no_unfold]
+            Name = String;
+
+            id-name (name : Name) : Name = name;
+        in
+            id-name
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).1,
+        support::parse_nf_term(&mut codemap, &context, expected_ty),
+    );
+}
+
+#[test]
+fn let_inline() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let given_expr = r#"
+        let
+            #[inline]
+            greeting = "hello";
+        in
+            greeting
+    "#;
+
+    assert_term_eq!(
+        support::parse_infer_term(&mut codemap, &context, given_expr).0,
+        support::parse_infer_term(&mut codemap, &context, r#""hello" : String"#).0,
+    );
+}
+
 #[test]
 fn array_intro_ambiguous() {
     let mut codemap = CodeMap::new();
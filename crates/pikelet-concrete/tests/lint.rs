@@ -0,0 +1,152 @@
+use codespan::CodeMap;
+
+use pikelet_concrete::lint::{self, Lint, Warning};
+
+mod support;
+
+fn lints(src: &str) -> Vec<Option<Lint>> {
+    let mut codemap = CodeMap::new();
+    let term = support::parse_term(&mut codemap, src);
+
+    lint::check_term(&term).iter().map(Warning::lint).collect()
+}
+
+#[test]
+fn unused_binder() {
+    assert_eq!(lints(r"\x y => x"), vec![Some(Lint::UnusedBinders)]);
+}
+
+#[test]
+fn unused_binder_underscore() {
+    assert_eq!(lints(r"\x _y => x"), vec![]);
+}
+
+#[test]
+fn unused_binder_definition_param() {
+    assert_eq!(
+        lints(r"let const a b (x : a) (y : b) = x; in const"),
+        vec![Some(Lint::UnusedBinders)],
+    );
+}
+
+#[test]
+fn unused_binder_used_in_where() {
+    assert_eq!(lints(r"let f x = y where { y = x; }; in f"), vec![]);
+}
+
+#[test]
+fn shadowing() {
+    assert_eq!(
+        lints(r"\x => \x => x"),
+        vec![Some(Lint::Shadowing), Some(Lint::UnusedBinders)]
+    );
+}
+
+#[test]
+fn shadowing_item_used_before_definition() {
+    assert_eq!(
+        lints(r"\x => let y = x; x = 2; in y"),
+        vec![Some(Lint::Shadowing)]
+    );
+}
+
+#[test]
+fn shadowing_record_label() {
+    assert_eq!(
+        lints(r"\x => Record { x : Type }"),
+        vec![Some(Lint::UnusedBinders)]
+    );
+}
+
+#[test]
+fn unused_import() {
+    assert_eq!(
+        lints(r#"let prim = import "prim"; in Type"#),
+        vec![Some(Lint::UnusedImports)],
+    );
+}
+
+#[test]
+fn used_import() {
+    assert_eq!(lints(r#"let prim = import "prim"; in prim"#), vec![]);
+}
+
+#[test]
+fn opened_import() {
+    let src = r#"let prim = import "prim"; open prim using (bool); in bool.eq"#;
+    assert_eq!(lints(src), vec![]);
+}
+
+#[test]
+fn allow_attribute() {
+    assert_eq!(
+        lints(r"let #[allow(unused-binders)] const a b (x : a) (y : b) = x; in const"),
+        vec![],
+    );
+}
+
+#[test]
+fn allow_attribute_on_declaration() {
+    let src = r"
+        let
+            #[allow(unused-binders)]
+            const : (a b : Type) -> a -> b -> a;
+            const a b x y = x;
+        in
+            const
+    ";
+
+    assert_eq!(lints(src), vec![]);
+}
+
+#[test]
+fn allow_attribute_unknown_lint() {
+    assert_eq!(lints(r"let #[allow(oops)] x = Type; in x"), vec![None]);
+}
+
+#[test]
+fn deprecated() {
+    let src = r#"
+        let
+            #[deprecated "use `b` instead"]
+            a = Type;
+            b = Type;
+        in
+            a
+    "#;
+
+    assert_eq!(lints(src), vec![Some(Lint::Deprecated)]);
+}
+
+#[test]
+fn deprecated_punned_field() {
+    let src = r#"
+        let
+            #[deprecated "use `b` instead"]
+            a = Type;
+        in
+            record { a }
+    "#;
+
+    assert_eq!(lints(src), vec![Some(Lint::Deprecated)]);
+}
+
+#[test]
+fn deprecated_allowed() {
+    let src = r"
+        let
+            #[deprecated]
+            a = Type;
+            #[allow(deprecated)]
+            b = a;
+        in
+            b
+    ";
+
+    assert_eq!(lints(src), vec![]);
+}
+
+#[test]
+fn malformed_attribute() {
+    assert_eq!(lints(r"let #[inline(oops)] x = Type; in x"), vec![None]);
+}
@@ -321,6 +321,42 @@ fn if_false() {
     );
 }
 
+#[test]
+fn char_code_points() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let given_expr = r#"
+        (import "prim/u32/to-char") ((import "prim/char/to-u32") '\u{1F600}')
+    "#;
+    let expected_expr = r#"
+        '😀'
+    "#;
+
+    assert_term_eq!(
+        support::parse_nf_term(&mut codemap, &context, given_expr),
+        support::parse_nf_term(&mut codemap, &context, expected_expr),
+    );
+}
+
+#[test]
+fn raw_string() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let given_expr = r###"
+        r#"a "\n" b"#
+    "###;
+    let expected_expr = r#"
+        "a \"\\n\" b"
+    "#;
+
+    assert_term_eq!(
+        support::parse_nf_term(&mut codemap, &context, given_expr),
+        support::parse_nf_term(&mut codemap, &context, expected_expr),
+    );
+}
+
 #[test]
 fn if_eval_cond() {
     let mut codemap = CodeMap::new();
@@ -393,3 +429,122 @@ fn record_ty_shadow() {
         support::parse_nf_term(&mut codemap, &context, expected_expr),
     );
 }
+
+#[test]
+fn term_too_large() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+    use pikelet_core::nbe::NbeError;
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+
+    let given_expr = r#"
+        let x0 = "hello";
+            x1 = record { l = x0; r = x0 };
+            x2 = record { l = x1; r = x1 };
+            x3 = record { l = x2; r = x2 };
+            x4 = record { l = x3; r = x3 };
+        in
+            x4
+    "#;
+
+    let term = support::parse_infer_term(&mut codemap, &context, given_expr).0;
+    context.set_limits(Limits {
+        max_size: 20,
+        ..Limits::default()
+    });
+
+    assert_eq!(
+        pikelet_core::nbe::nf_term(&context, &term),
+        Err(NbeError::TooLarge(LimitExceeded::Size { max_size: 20 })),
+    );
+}
+
+#[test]
+fn term_too_deep() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+    use pikelet_core::nbe::NbeError;
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+
+    let given_expr = r#"
+        let id = \(a : Type) (x : a) => x;
+        in
+            id String (id String (id String (id String "hello")))
+    "#;
+
+    let term = support::parse_infer_term(&mut codemap, &context, given_expr).0;
+
+    // The depth of evaluation is limited separately from the depth that
+    // terms can be nested to
+    context.set_limits(Limits {
+        max_depth: 1,
+        ..Limits::default()
+    });
+    assert!(pikelet_core::nbe::nf_term(&context, &term).is_ok());
+
+    context.set_limits(Limits {
+        max_eval_depth: 4,
+        ..Limits::default()
+    });
+    assert_eq!(
+        pikelet_core::nbe::nf_term(&context, &term),
+        Err(NbeError::TooLarge(LimitExceeded::EvalDepth { max_eval_depth: 4 })),
+    );
+}
+
+#[test]
+fn definition_nf_cached() {
+    use pikelet_core::limits::Limits;
+
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+
+    let x = FreeVar::fresh_named("x");
+    let (term, ty) =
+        support::parse_infer_term(&mut codemap, &context, r#"(\y : String => y) "hello""#);
+    context.insert_declaration(x.clone(), ty);
+    context.insert_definition(x.clone(), term);
+
+    let var = RcTerm::from(Term::var(Var::Free(x), 0));
+    let expected = support::parse_nf_term(&mut codemap, &context, r#""hello""#);
+    assert_eq!(
+        pikelet_core::nbe::nf_term(&context, &var),
+        Ok(expected.clone())
+    );
+
+    // The definition should not need to be normalized again
+    context.set_limits(Limits {
+        max_size: 1,
+        ..Limits::default()
+    });
+    assert_eq!(pikelet_core::nbe::nf_term(&context, &var), Ok(expected));
+}
+
+#[test]
+fn definition_nf_redefined() {
+    let mut codemap = CodeMap::new();
+    let mut context = Context::default();
+
+    let x = FreeVar::fresh_named("x");
+    let var = RcTerm::from(Term::var(Var::Free(x.clone()), 0));
+
+    let (term, _) = support::parse_infer_term(&mut codemap, &context, r#""hello""#);
+    context.insert_definition(x.clone(), term);
+    assert_eq!(
+        pikelet_core::nbe::nf_term(&context, &var),
+        Ok(support::parse_nf_term(&mut codemap, &context, r#""hello""#)),
+    );
+
+    let (term, _) = support::parse_infer_term(&mut codemap, &context, r#""goodbye""#);
+    context.insert_definition(x, term);
+    assert_eq!(
+        pikelet_core::nbe::nf_term(&context, &var),
+        Ok(support::parse_nf_term(
+            &mut codemap,
+            &context,
+            r#""goodbye""#
+        )),
+    );
+}
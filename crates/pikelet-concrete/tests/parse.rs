@@ -84,3 +84,325 @@ fn integer_overflow() {
         )
     );
 }
+
+#[test]
+fn term_too_deep() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+
+    let src = "((((Type))))";
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+    let limits = Limits {
+        max_depth: 3,
+        ..Limits::default()
+    };
+
+    let parse_result = parse::term_with_limits(&filemap, limits);
+
+    assert_eq!(
+        parse_result,
+        (
+            concrete::Term::Error(ByteSpan::new(ByteIndex(1), ByteIndex(13))),
+            vec![],
+            vec![ParseError::TermTooLarge {
+                span: ByteSpan::new(ByteIndex(2), ByteIndex(12)),
+                limit: LimitExceeded::Depth { max_depth: 3 },
+            }],
+        )
+    );
+}
+
+/// Parse a term that is far too deeply nested, returning the errors
+fn parse_deep_term(src: &str) -> Vec<ParseError> {
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+
+    let (term, _, errors) = parse::term(&filemap);
+
+    assert_eq!(term, concrete::Term::Error(filemap.span()));
+    errors
+}
+
+fn repeat(s: &str, count: usize) -> String {
+    (0..count).map(|_| s).collect()
+}
+
+#[test]
+fn term_too_deep_parens() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+
+    let depth = 100_000;
+    let src = format!("{}x{}", repeat("(", depth), repeat(")", depth));
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge {
+            limit: LimitExceeded::Depth { max_depth },
+            ..
+        }] => assert_eq!(max_depth, Limits::default().max_depth),
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_deep_arrows() {
+    let src = format!("{}x", repeat("x -> ", 100_000));
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge { .. }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_deep_fun_ty_ann() {
+    let depth = 100_000;
+    let src = format!("(x : {}x{}) -> x", repeat("(", depth), repeat(")", depth));
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge { .. }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_deep_fun_intro_params() {
+    use pikelet_core::limits::LimitExceeded;
+
+    let params = (0..20_000).map(|i| format!(" x{}", i)).collect::<String>();
+    let src = format!("\\{} => Type", params);
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge {
+            limit: LimitExceeded::Depth { .. },
+            ..
+        }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_deep_fun_ty_params() {
+    use pikelet_core::limits::LimitExceeded;
+
+    let params = (0..20_000)
+        .map(|i| format!("(x{} : Type) ", i))
+        .collect::<String>();
+    let src = format!("{}-> Type", params);
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge {
+            limit: LimitExceeded::Depth { .. },
+            ..
+        }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_deep_definition_params() {
+    use pikelet_core::limits::LimitExceeded;
+
+    let params = (0..20_000).map(|i| format!(" x{}", i)).collect::<String>();
+    let src = format!("let f{} = Type; in f", params);
+
+    match parse_deep_term(&src)[..] {
+        [ParseError::TermTooLarge {
+            limit: LimitExceeded::Depth { .. },
+            ..
+        }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+/// Parse a term that has too many arguments or items
+fn parse_wide_term(src: &str) -> Vec<ParseError> {
+    use pikelet_core::limits::Limits;
+
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+    let limits = Limits {
+        max_size: 10_000,
+        ..Limits::default()
+    };
+
+    let (term, _, errors) = parse::term_with_limits(&filemap, limits);
+
+    assert_eq!(term, concrete::Term::Error(filemap.span()));
+    errors
+}
+
+fn assert_too_large(errors: &[ParseError]) {
+    use pikelet_core::limits::LimitExceeded;
+
+    match *errors {
+        [ParseError::TermTooLarge {
+            limit: LimitExceeded::Size { max_size: 10_000 },
+            ..
+        }] => {},
+        ref errors => panic!("unexpected errors: {:?}", errors),
+    }
+}
+
+#[test]
+fn term_too_large_fun_app() {
+    let src = format!("(\\x => x){}", repeat(" Type", 20_000));
+
+    assert_too_large(&parse_wide_term(&src));
+}
+
+#[test]
+fn term_too_large_let_items() {
+    let items = (0..20_000)
+        .map(|i| format!("x{} = Type; ", i))
+        .collect::<String>();
+    let src = format!("let {}in Type", items);
+
+    assert_too_large(&parse_wide_term(&src));
+}
+
+#[test]
+fn wide_terms_are_not_too_deep() {
+    use pikelet_core::limits::Limits;
+
+    // Arguments and items are not counted toward the depth
+    let count = Limits::default().max_depth * 2;
+    let items = (0..count)
+        .map(|i| format!("x{} = Type; ", i))
+        .collect::<String>();
+    let src = format!("let {}in f{}", items, repeat(" Type", count));
+
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src);
+    let (_, _, errors) = parse::term(&filemap);
+
+    assert_eq!(errors, vec![]);
+}
+
+#[test]
+fn term_too_large() {
+    use pikelet_core::limits::{LimitExceeded, Limits};
+
+    let src = "[Type; Type; Type; Type]";
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+    let limits = Limits {
+        max_size: 4,
+        ..Limits::default()
+    };
+
+    let parse_result = parse::term_with_limits(&filemap, limits);
+
+    assert_eq!(
+        parse_result,
+        (
+            concrete::Term::Error(ByteSpan::new(ByteIndex(1), ByteIndex(25))),
+            vec![],
+            vec![ParseError::TermTooLarge {
+                span: ByteSpan::new(ByteIndex(1), ByteIndex(25)),
+                limit: LimitExceeded::Size { max_size: 4 },
+            }],
+        )
+    );
+}
+
+#[test]
+fn term_too_deep_syntax_error() {
+    // The parser gives up on the deeply nested term before it reaches the
+    // syntax error
+    let depth = 100_000;
+    let src = format!("{}x{} )", repeat("(", depth), repeat(")", depth));
+
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src);
+    let (_, _, errors) = parse::term(&filemap);
+
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn open_using() {
+    let src = "let open foo using (bar, baz); in bar";
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+
+    let (term, _, errors) = parse::term(&filemap);
+
+    assert_eq!(errors, vec![]);
+    match term {
+        concrete::Term::Let(_, ref items, _) => match items[..] {
+            [concrete::Item::Open {
+                module: concrete::Term::Name(_, ref module, None),
+                using: Some(ref using),
+                ..
+            }] => {
+                assert_eq!(module, "foo");
+                assert_eq!(
+                    using,
+                    &[
+                        (ByteIndex(21), "bar".to_owned()),
+                        (ByteIndex(26), "baz".to_owned())
+                    ],
+                );
+            },
+            ref items => panic!("unexpected items: {:?}", items),
+        },
+        ref term => panic!("unexpected term: {}", term),
+    }
+}
+
+#[test]
+fn where_ann() {
+    // The annotation binds tighter than the where clause
+    let src = r#"x : String where { x = "hello"; }"#;
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+
+    let (term, _, errors) = parse::term(&filemap);
+
+    assert_eq!(errors, vec![]);
+    match term {
+        concrete::Term::Where(ref expr, ref items, _) => {
+            match **expr {
+                concrete::Term::Ann(ref expr, ref ty) => match (&**expr, &**ty) {
+                    (concrete::Term::Name(_, x, None), concrete::Term::Name(_, ty, None)) => {
+                        assert_eq!(x, "x");
+                        assert_eq!(ty, "String");
+                    },
+                    _ => panic!("unexpected annotation: {}", expr),
+                },
+                ref expr => panic!("unexpected expression: {}", expr),
+            }
+            assert_eq!(items.len(), 1);
+        },
+        ref term => panic!("unexpected term: {}", term),
+    }
+}
+
+#[test]
+fn definition_where_ann() {
+    // The where clause belongs to the definition, not to the annotation
+    let src = r#"let f = x : String where { x = "hello"; }; in f"#;
+    let mut codemap = CodeMap::new();
+    let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+
+    let (term, _, errors) = parse::term(&filemap);
+
+    assert_eq!(errors, vec![]);
+    match term {
+        concrete::Term::Let(_, ref items, _) => match items[..] {
+            [concrete::Item::Definition {
+                body: concrete::Term::Ann(_, ref ty),
+                ref where_items,
+                ..
+            }] => {
+                match **ty {
+                    concrete::Term::Name(_, ref ty, None) => assert_eq!(ty, "String"),
+                    ref ty => panic!("unexpected type: {}", ty),
+                }
+                assert_eq!(where_items.len(), 1);
+            },
+            ref items => panic!("unexpected items: {:?}", items),
+        },
+        ref term => panic!("unexpected term: {}", term),
+    }
+}
@@ -1,11 +1,14 @@
-use codespan::{ByteIndex, ByteSpan};
-use moniker::{Binder, Embed, FreeVar, Nest, Scope, Var};
+use codespan::{ByteIndex, ByteSpan, CodeMap};
+use moniker::{assert_term_eq, Binder, Embed, FreeVar, Nest, Scope, Var};
 use pretty_assertions::assert_eq;
 
-use pikelet_concrete::resugar::{Resugar, ResugarEnv};
+use pikelet_concrete::elaborate::Context;
+use pikelet_concrete::resugar::{Resugar, ResugarEnv, OPENED_NAME};
 use pikelet_concrete::syntax::concrete;
 use pikelet_core::syntax::{core, Label, LevelShift, Literal};
 
+mod support;
+
 fn span() -> ByteSpan {
     ByteSpan::default()
 }
@@ -184,16 +187,20 @@ fn let_shadow_keyword() {
         index(),
         vec![
             concrete::Item::Definition {
+                attrs: vec![],
                 name: (index(), "else1".to_owned()),
                 params: vec![],
                 return_ann: None,
                 body: concrete::Term::Universe(span(), None),
+                where_items: vec![],
             },
             concrete::Item::Definition {
+                attrs: vec![],
                 name: (index(), "else2".to_owned()),
                 params: vec![],
                 return_ann: None,
                 body: concrete::Term::Universe(span(), None),
+                where_items: vec![],
             },
         ],
         Box::new(concrete::Term::RecordIntro(span(), vec![])),
@@ -202,6 +209,85 @@ fn let_shadow_keyword() {
     assert_eq!(core_module.resugar(&ResugarEnv::new()), concrete_module);
 }
 
+#[test]
+fn let_open_round_trip() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let given_expr = r#"
+        let
+            open record { hello = "hello"; goodbye = "goodbye"; unused = "unused" };
+        in
+            record { a = hello; b = goodbye; c = hello }
+    "#;
+    let expected_expr = concat!(
+        r#"let open record { hello = "hello"; goodbye = "goodbye"; unused = "unused" } "#,
+        r#"using (hello, goodbye); in record { a = hello; b = goodbye; c = hello }"#,
+    );
+
+    let term = support::parse_infer_term(&mut codemap, &context, given_expr).0;
+    let resugared = context.resugar(&term).to_string();
+    assert_eq!(resugared, expected_expr);
+
+    assert_term_eq!(
+        support::parse_nf_term(&mut codemap, &context, &resugared),
+        support::parse_nf_term(&mut codemap, &context, given_expr),
+    );
+}
+
+#[test]
+fn let_open_captured() {
+    let var_x = FreeVar::fresh_named("x");
+    let var_open = FreeVar::fresh_named(OPENED_NAME);
+    let mut env = ResugarEnv::new();
+    env.on_item(&Label("x".to_owned()), &Binder(var_x));
+
+    // The field can't be referred to as `x`, as that is already in scope
+    let core_term = core::Term::Let(Scope::new(
+        Nest::new(vec![(
+            Binder(var_open.clone()),
+            Embed(core::RcTerm::from(core::Term::RecordIntro(vec![(
+                Label("x".to_owned()),
+                core::RcTerm::from(core::Term::universe(0)),
+            )]))),
+        )]),
+        core::RcTerm::from(core::Term::RecordProj(
+            core::RcTerm::from(core::Term::var(Var::Free(var_open), 0)),
+            Label("x".to_owned()),
+            LevelShift(0),
+        )),
+    ));
+
+    let concrete_term = concrete::Term::Let(
+        index(),
+        vec![concrete::Item::Definition {
+            attrs: vec![],
+            name: (index(), "open1".to_owned()),
+            params: vec![],
+            return_ann: None,
+            body: concrete::Term::RecordIntro(
+                span(),
+                vec![concrete::RecordIntroField::Explicit {
+                    label: (index(), "x".to_owned()),
+                    params: vec![],
+                    return_ann: None,
+                    term: concrete::Term::Universe(span(), None),
+                }],
+            ),
+            where_items: vec![],
+        }],
+        Box::new(concrete::Term::RecordProj(
+            span(),
+            Box::new(concrete::Term::Name(span(), "open1".to_owned(), None)),
+            index(),
+            "x".to_owned(),
+            None,
+        )),
+    );
+
+    assert_eq!(core_term.resugar(&env), concrete_term);
+}
+
 #[test]
 fn record_ty_empty() {
     let core_term = core::Term::RecordType(Scope::new(Nest::new(vec![]), ()));
@@ -0,0 +1,59 @@
+use codespan::CodeMap;
+use moniker::assert_term_eq;
+
+use pikelet_concrete::elaborate::Context;
+use pikelet_core::syntax::core::{RcTerm, Term};
+use pikelet_core::syntax::Literal;
+use pikelet_core::zipper::Zipper;
+
+mod support;
+
+#[test]
+fn navigate() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let (term, _) =
+        support::parse_infer_term(&mut codemap, &context, r#"record { x = "a"; y = "b" }"#);
+    let mut zipper = Zipper::new(term);
+
+    assert!(!zipper.up());
+    assert!(!zipper.right());
+    assert!(zipper.down());
+    assert_eq!(zipper.depth(), 1);
+    assert_eq!(zipper.index(), Some(0));
+    assert!(zipper.right());
+    assert_eq!(zipper.index(), Some(1));
+    assert_term_eq!(
+        *zipper.focus(),
+        RcTerm::from(Term::Literal(Literal::String("b".to_owned()))),
+    );
+    assert!(!zipper.right());
+    assert!(!zipper.down());
+    assert!(zipper.left());
+    assert_term_eq!(
+        *zipper.focus(),
+        RcTerm::from(Term::Literal(Literal::String("a".to_owned()))),
+    );
+    assert!(!zipper.left());
+    assert!(zipper.up());
+    assert_eq!(zipper.depth(), 0);
+}
+
+#[test]
+fn replace_under_binder() {
+    let mut codemap = CodeMap::new();
+    let context = Context::default();
+
+    let (term, _) = support::parse_infer_term(&mut codemap, &context, r"\x : String => x");
+    let (expected, _) =
+        support::parse_infer_term(&mut codemap, &context, r#"\x : String => "hello""#);
+
+    let mut zipper = Zipper::new(term);
+    assert!(zipper.down_nth(1));
+    zipper.replace(RcTerm::from(Term::Literal(Literal::String(
+        "hello".to_owned(),
+    ))));
+
+    assert_term_eq!(zipper.into_term(), expected);
+}
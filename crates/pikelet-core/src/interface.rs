@@ -0,0 +1,658 @@
+//! A compact binary format for the interfaces of checked modules
+//!
+//! Interfaces are stored in `.lpi` files, and contain the elaborated type of
+//! a module, and optionally its body. Loading an interface is much cheaper
+//! than type checking the module's source code again.
+//!
+//! Free variables, for example references to the built-in definitions, are
+//! stored by name, and are resolved against a set of mappings when the
+//! interface is loaded. Binders are given fresh names on loading.
+//!
+//! All integers are stored as unsigned LEB128 variable length integers, with
+//! signed integers being zig-zag encoded first.
+//!
+//! Interface files may be corrupt or crafted, so terms are not decoded past
+//! the maximum depth given in the `Limits`.
+
+use failure::Fail;
+use moniker::{Binder, BinderIndex, BoundVar, Embed, FreeVar, Nest, Scope, ScopeOffset, Var};
+
+use crate::limits::{LimitExceeded, Limits};
+use crate::syntax::core::{Pattern, RcPattern, RcTerm, Term};
+use crate::syntax::{Label, Level, LevelShift, Literal};
+
+/// The bytes that every interface file starts with
+pub const MAGIC: &[u8; 4] = b"LPI\0";
+
+/// The version of the interface format. This should be incremented whenever
+/// the format changes in an incompatible way.
+pub const VERSION: u32 = 1;
+
+/// The file extension used for interface files
+pub const EXTENSION: &str = "lpi";
+
+/// The interface of a checked module
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    /// The elaborated type of the module
+    pub ty: RcTerm,
+    /// The elaborated body of the module, if it was included
+    pub body: Option<RcTerm>,
+}
+
+/// An error encountered while decoding an interface
+#[derive(Debug, Clone, PartialEq, Fail)]
+pub enum DecodeError {
+    #[fail(display = "not an interface file")]
+    BadMagic,
+    #[fail(
+        display = "unsupported interface version {}, expected version {}",
+        found, expected
+    )]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[fail(display = "unexpected end of interface file")]
+    UnexpectedEof,
+    #[fail(display = "invalid {} tag: {}", kind, tag)]
+    InvalidTag { kind: &'static str, tag: u8 },
+    #[fail(display = "invalid {} in interface file", kind)]
+    InvalidValue { kind: &'static str },
+    #[fail(display = "unknown variable `{}` in interface file", name)]
+    UnknownVar { name: String },
+    #[fail(display = "unexpected trailing bytes in interface file")]
+    TrailingBytes,
+    #[fail(display = "term too large: {}", _0)]
+    TooLarge(#[cause] LimitExceeded),
+}
+
+impl Interface {
+    /// Encode the interface into the binary format
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = Encoder { bytes: Vec::new() };
+
+        encoder.bytes.extend_from_slice(MAGIC);
+        encoder.u64(u64::from(VERSION));
+        encoder.term(&self.ty);
+        match self.body {
+            None => encoder.u8(0),
+            Some(ref body) => {
+                encoder.u8(1);
+                encoder.term(body);
+            },
+        }
+
+        encoder.bytes
+    }
+
+    /// Decode an interface from the binary format, resolving any free
+    /// variables using the `lookup` function, and rejecting terms that are
+    /// nested deeper than the given `limits`
+    pub fn decode<F>(bytes: &[u8], limits: Limits, lookup: F) -> Result<Interface, DecodeError>
+    where
+        F: Fn(&str) -> Option<FreeVar<String>>,
+    {
+        let mut decoder = Decoder {
+            bytes,
+            lookup,
+            depth: 0,
+            max_depth: limits.max_depth,
+        };
+
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = decoder.u32()?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion {
+                found: version,
+                expected: VERSION,
+            });
+        }
+        let ty = decoder.term()?;
+        let body = match decoder.u8()? {
+            0 => None,
+            1 => Some(decoder.term()?),
+            tag => return Err(DecodeError::InvalidTag { kind: "body", tag }),
+        };
+        if !decoder.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(Interface { ty, body })
+    }
+}
+
+mod tag {
+    pub const TERM_ANN: u8 = 0;
+    pub const TERM_UNIVERSE: u8 = 1;
+    pub const TERM_LITERAL: u8 = 2;
+    pub const TERM_VAR: u8 = 3;
+    pub const TERM_IMPORT: u8 = 4;
+    pub const TERM_FUN_TYPE: u8 = 5;
+    pub const TERM_FUN_INTRO: u8 = 6;
+    pub const TERM_FUN_APP: u8 = 7;
+    pub const TERM_RECORD_TYPE: u8 = 8;
+    pub const TERM_RECORD_INTRO: u8 = 9;
+    pub const TERM_RECORD_PROJ: u8 = 10;
+    pub const TERM_CASE: u8 = 11;
+    pub const TERM_ARRAY_INTRO: u8 = 12;
+    pub const TERM_LET: u8 = 13;
+
+    pub const PATTERN_ANN: u8 = 0;
+    pub const PATTERN_BINDER: u8 = 1;
+    pub const PATTERN_VAR: u8 = 2;
+    pub const PATTERN_LITERAL: u8 = 3;
+
+    pub const VAR_FREE: u8 = 0;
+    pub const VAR_BOUND: u8 = 1;
+
+    pub const LITERAL_BOOL: u8 = 0;
+    pub const LITERAL_STRING: u8 = 1;
+    pub const LITERAL_CHAR: u8 = 2;
+    pub const LITERAL_U8: u8 = 3;
+    pub const LITERAL_U16: u8 = 4;
+    pub const LITERAL_U32: u8 = 5;
+    pub const LITERAL_U64: u8 = 6;
+    pub const LITERAL_S8: u8 = 7;
+    pub const LITERAL_S16: u8 = 8;
+    pub const LITERAL_S32: u8 = 9;
+    pub const LITERAL_S64: u8 = 10;
+    pub const LITERAL_F32: u8 = 11;
+    pub const LITERAL_F64: u8 = 12;
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u64(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.u64(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn name(&mut self, name: &Option<String>) {
+        match *name {
+            None => self.u8(0),
+            Some(ref name) => {
+                self.u8(1);
+                self.string(name);
+            },
+        }
+    }
+
+    fn binder(&mut self, binder: &Binder<String>) {
+        self.name(&binder.0.pretty_name);
+    }
+
+    fn var(&mut self, var: &Var<String>) {
+        match *var {
+            Var::Free(ref free_var) => {
+                self.u8(tag::VAR_FREE);
+                self.name(&free_var.pretty_name);
+            },
+            Var::Bound(ref bound_var) => {
+                self.u8(tag::VAR_BOUND);
+                self.u64(u64::from(bound_var.scope.0));
+                self.u64(u64::from(bound_var.binder.0));
+                self.name(&bound_var.pretty_name);
+            },
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal) {
+        match *literal {
+            Literal::Bool(value) => {
+                self.u8(tag::LITERAL_BOOL);
+                self.u8(value as u8);
+            },
+            Literal::String(ref value) => {
+                self.u8(tag::LITERAL_STRING);
+                self.string(value);
+            },
+            Literal::Char(value) => {
+                self.u8(tag::LITERAL_CHAR);
+                self.u64(u64::from(u32::from(value)));
+            },
+            Literal::U8(value) => {
+                self.u8(tag::LITERAL_U8);
+                self.u64(u64::from(value));
+            },
+            Literal::U16(value) => {
+                self.u8(tag::LITERAL_U16);
+                self.u64(u64::from(value));
+            },
+            Literal::U32(value) => {
+                self.u8(tag::LITERAL_U32);
+                self.u64(u64::from(value));
+            },
+            Literal::U64(value) => {
+                self.u8(tag::LITERAL_U64);
+                self.u64(value);
+            },
+            Literal::S8(value) => {
+                self.u8(tag::LITERAL_S8);
+                self.i64(i64::from(value));
+            },
+            Literal::S16(value) => {
+                self.u8(tag::LITERAL_S16);
+                self.i64(i64::from(value));
+            },
+            Literal::S32(value) => {
+                self.u8(tag::LITERAL_S32);
+                self.i64(i64::from(value));
+            },
+            Literal::S64(value) => {
+                self.u8(tag::LITERAL_S64);
+                self.i64(value);
+            },
+            Literal::F32(value) => {
+                self.u8(tag::LITERAL_F32);
+                self.bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+            },
+            Literal::F64(value) => {
+                self.u8(tag::LITERAL_F64);
+                self.bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+            },
+        }
+    }
+
+    fn pattern(&mut self, pattern: &RcPattern) {
+        match *pattern.inner {
+            Pattern::Ann(ref pattern, Embed(ref ty)) => {
+                self.u8(tag::PATTERN_ANN);
+                self.pattern(pattern);
+                self.term(ty);
+            },
+            Pattern::Binder(ref binder) => {
+                self.u8(tag::PATTERN_BINDER);
+                self.binder(binder);
+            },
+            Pattern::Var(Embed(ref var), shift) => {
+                self.u8(tag::PATTERN_VAR);
+                self.var(var);
+                self.u64(u64::from(shift.0));
+            },
+            Pattern::Literal(ref literal) => {
+                self.u8(tag::PATTERN_LITERAL);
+                self.literal(literal);
+            },
+        }
+    }
+
+    fn term(&mut self, term: &RcTerm) {
+        match *term.inner {
+            Term::Ann(ref term, ref ty) => {
+                self.u8(tag::TERM_ANN);
+                self.term(term);
+                self.term(ty);
+            },
+            Term::Universe(level) => {
+                self.u8(tag::TERM_UNIVERSE);
+                self.u64(u64::from(level.0));
+            },
+            Term::Literal(ref literal) => {
+                self.u8(tag::TERM_LITERAL);
+                self.literal(literal);
+            },
+            Term::Var(ref var, shift) => {
+                self.u8(tag::TERM_VAR);
+                self.var(var);
+                self.u64(u64::from(shift.0));
+            },
+            Term::Import(ref name) => {
+                self.u8(tag::TERM_IMPORT);
+                self.string(name);
+            },
+            Term::FunType(ref scope) | Term::FunIntro(ref scope) => {
+                let (ref binder, Embed(ref ann)) = scope.unsafe_pattern;
+                match *term.inner {
+                    Term::FunType(_) => self.u8(tag::TERM_FUN_TYPE),
+                    _ => self.u8(tag::TERM_FUN_INTRO),
+                }
+                self.binder(binder);
+                self.term(ann);
+                self.term(&scope.unsafe_body);
+            },
+            Term::FunApp(ref head, ref arg) => {
+                self.u8(tag::TERM_FUN_APP);
+                self.term(head);
+                self.term(arg);
+            },
+            Term::RecordType(ref scope) => {
+                self.u8(tag::TERM_RECORD_TYPE);
+                self.usize(scope.unsafe_pattern.unsafe_patterns.len());
+                for &(ref label, ref binder, Embed(ref ann)) in
+                    &scope.unsafe_pattern.unsafe_patterns
+                {
+                    self.string(&label.0);
+                    self.binder(binder);
+                    self.term(ann);
+                }
+            },
+            Term::RecordIntro(ref fields) => {
+                self.u8(tag::TERM_RECORD_INTRO);
+                self.usize(fields.len());
+                for &(ref label, ref term) in fields {
+                    self.string(&label.0);
+                    self.term(term);
+                }
+            },
+            Term::RecordProj(ref expr, ref label, shift) => {
+                self.u8(tag::TERM_RECORD_PROJ);
+                self.term(expr);
+                self.string(&label.0);
+                self.u64(u64::from(shift.0));
+            },
+            Term::Case(ref head, ref clauses) => {
+                self.u8(tag::TERM_CASE);
+                self.term(head);
+                self.usize(clauses.len());
+                for clause in clauses {
+                    self.pattern(&clause.unsafe_pattern);
+                    self.term(&clause.unsafe_body);
+                }
+            },
+            Term::ArrayIntro(ref elems) => {
+                self.u8(tag::TERM_ARRAY_INTRO);
+                self.usize(elems.len());
+                for elem in elems {
+                    self.term(elem);
+                }
+            },
+            Term::Let(ref scope) => {
+                self.u8(tag::TERM_LET);
+                self.usize(scope.unsafe_pattern.unsafe_patterns.len());
+                for &(ref binder, Embed(ref value)) in &scope.unsafe_pattern.unsafe_patterns {
+                    self.binder(binder);
+                    self.term(value);
+                }
+                self.term(&scope.unsafe_body);
+            },
+        }
+    }
+}
+
+struct Decoder<'a, F> {
+    bytes: &'a [u8],
+    lookup: F,
+    /// The number of terms and patterns currently being decoded
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a, F> Decoder<'a, F>
+where
+    F: Fn(&str) -> Option<FreeVar<String>>,
+{
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::InvalidValue { kind: "integer" })
+    }
+
+    fn i64(&mut self) -> Result<i64, DecodeError> {
+        let value = self.u64()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let value = self.u64()?;
+        if value > u64::from(u32::MAX) {
+            return Err(DecodeError::InvalidValue { kind: "integer" });
+        }
+        Ok(value as u32)
+    }
+
+    fn usize(&mut self) -> Result<usize, DecodeError> {
+        let value = self.u64()?;
+        if value > self.bytes.len() as u64 {
+            // Every element takes up at least one byte, so this length
+            // can't be right - bail out before allocating anything
+            return Err(DecodeError::UnexpectedEof);
+        }
+        Ok(value as usize)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.usize()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidValue { kind: "string" })
+    }
+
+    fn name(&mut self) -> Result<Option<String>, DecodeError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.string()?)),
+            tag => Err(DecodeError::InvalidTag { kind: "name", tag }),
+        }
+    }
+
+    fn binder(&mut self) -> Result<Binder<String>, DecodeError> {
+        Ok(Binder(FreeVar::fresh(self.name()?)))
+    }
+
+    fn var(&mut self) -> Result<Var<String>, DecodeError> {
+        match self.u8()? {
+            tag::VAR_FREE => match self.name()? {
+                None => Err(DecodeError::InvalidValue {
+                    kind: "free variable",
+                }),
+                Some(name) => match (self.lookup)(&name) {
+                    Some(free_var) => Ok(Var::Free(free_var)),
+                    None => Err(DecodeError::UnknownVar { name }),
+                },
+            },
+            tag::VAR_BOUND => Ok(Var::Bound(BoundVar {
+                scope: ScopeOffset(self.u32()?),
+                binder: BinderIndex(self.u32()?),
+                pretty_name: self.name()?,
+            })),
+            tag => Err(DecodeError::InvalidTag {
+                kind: "variable",
+                tag,
+            }),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal, DecodeError> {
+        use std::convert::TryFrom;
+
+        fn convert<T: TryFrom<U>, U>(value: U) -> Result<T, DecodeError> {
+            T::try_from(value).map_err(|_| DecodeError::InvalidValue { kind: "literal" })
+        }
+
+        match self.u8()? {
+            tag::LITERAL_BOOL => match self.u8()? {
+                0 => Ok(Literal::Bool(false)),
+                1 => Ok(Literal::Bool(true)),
+                _ => Err(DecodeError::InvalidValue { kind: "literal" }),
+            },
+            tag::LITERAL_STRING => Ok(Literal::String(self.string()?)),
+            tag::LITERAL_CHAR => Ok(Literal::Char(convert(self.u32()?)?)),
+            tag::LITERAL_U8 => Ok(Literal::U8(convert(self.u64()?)?)),
+            tag::LITERAL_U16 => Ok(Literal::U16(convert(self.u64()?)?)),
+            tag::LITERAL_U32 => Ok(Literal::U32(convert(self.u64()?)?)),
+            tag::LITERAL_U64 => Ok(Literal::U64(self.u64()?)),
+            tag::LITERAL_S8 => Ok(Literal::S8(convert(self.i64()?)?)),
+            tag::LITERAL_S16 => Ok(Literal::S16(convert(self.i64()?)?)),
+            tag::LITERAL_S32 => Ok(Literal::S32(convert(self.i64()?)?)),
+            tag::LITERAL_S64 => Ok(Literal::S64(self.i64()?)),
+            tag::LITERAL_F32 => {
+                let bytes = <[u8; 4]>::try_from(self.take(4)?).unwrap();
+                Ok(Literal::F32(f32::from_bits(u32::from_le_bytes(bytes))))
+            },
+            tag::LITERAL_F64 => {
+                let bytes = <[u8; 8]>::try_from(self.take(8)?).unwrap();
+                Ok(Literal::F64(f64::from_bits(u64::from_le_bytes(bytes))))
+            },
+            tag => Err(DecodeError::InvalidTag {
+                kind: "literal",
+                tag,
+            }),
+        }
+    }
+
+    /// Run `f` one level deeper in the decoded term, failing if this would
+    /// exceed the maximum depth
+    fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, DecodeError>,
+    ) -> Result<T, DecodeError> {
+        if self.depth >= self.max_depth {
+            let max_depth = self.max_depth;
+            return Err(DecodeError::TooLarge(LimitExceeded::Depth { max_depth }));
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn pattern(&mut self) -> Result<RcPattern, DecodeError> {
+        self.nested(Self::pattern_node)
+    }
+
+    fn pattern_node(&mut self) -> Result<RcPattern, DecodeError> {
+        let pattern = match self.u8()? {
+            tag::PATTERN_ANN => Pattern::Ann(self.pattern()?, Embed(self.term()?)),
+            tag::PATTERN_BINDER => Pattern::Binder(self.binder()?),
+            tag::PATTERN_VAR => Pattern::Var(Embed(self.var()?), LevelShift(self.u32()?)),
+            tag::PATTERN_LITERAL => Pattern::Literal(self.literal()?),
+            tag => {
+                return Err(DecodeError::InvalidTag {
+                    kind: "pattern",
+                    tag,
+                })
+            },
+        };
+
+        Ok(RcPattern::from(pattern))
+    }
+
+    fn term(&mut self) -> Result<RcTerm, DecodeError> {
+        self.nested(Self::term_node)
+    }
+
+    fn term_node(&mut self) -> Result<RcTerm, DecodeError> {
+        let term = match self.u8()? {
+            tag::TERM_ANN => Term::Ann(self.term()?, self.term()?),
+            tag::TERM_UNIVERSE => Term::Universe(Level(self.u32()?)),
+            tag::TERM_LITERAL => Term::Literal(self.literal()?),
+            tag::TERM_VAR => Term::Var(self.var()?, LevelShift(self.u32()?)),
+            tag::TERM_IMPORT => Term::Import(self.string()?),
+            tag @ tag::TERM_FUN_TYPE | tag @ tag::TERM_FUN_INTRO => {
+                let scope = Scope {
+                    unsafe_pattern: (self.binder()?, Embed(self.term()?)),
+                    unsafe_body: self.term()?,
+                };
+                match tag {
+                    tag::TERM_FUN_TYPE => Term::FunType(scope),
+                    _ => Term::FunIntro(scope),
+                }
+            },
+            tag::TERM_FUN_APP => Term::FunApp(self.term()?, self.term()?),
+            tag::TERM_RECORD_TYPE => {
+                let len = self.usize()?;
+                let mut unsafe_patterns = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let label = Label(self.string()?);
+                    unsafe_patterns.push((label, self.binder()?, Embed(self.term()?)));
+                }
+                Term::RecordType(Scope {
+                    unsafe_pattern: Nest { unsafe_patterns },
+                    unsafe_body: (),
+                })
+            },
+            tag::TERM_RECORD_INTRO => {
+                let len = self.usize()?;
+                let mut fields = Vec::with_capacity(len);
+                for _ in 0..len {
+                    fields.push((Label(self.string()?), self.term()?));
+                }
+                Term::RecordIntro(fields)
+            },
+            tag::TERM_RECORD_PROJ => {
+                Term::RecordProj(self.term()?, Label(self.string()?), LevelShift(self.u32()?))
+            },
+            tag::TERM_CASE => {
+                let head = self.term()?;
+                let len = self.usize()?;
+                let mut clauses = Vec::with_capacity(len);
+                for _ in 0..len {
+                    clauses.push(Scope {
+                        unsafe_pattern: self.pattern()?,
+                        unsafe_body: self.term()?,
+                    });
+                }
+                Term::Case(head, clauses)
+            },
+            tag::TERM_ARRAY_INTRO => {
+                let len = self.usize()?;
+                let mut elems = Vec::with_capacity(len);
+                for _ in 0..len {
+                    elems.push(self.term()?);
+                }
+                Term::ArrayIntro(elems)
+            },
+            tag::TERM_LET => {
+                let len = self.usize()?;
+                let mut unsafe_patterns = Vec::with_capacity(len);
+                for _ in 0..len {
+                    unsafe_patterns.push((self.binder()?, Embed(self.term()?)));
+                }
+                Term::Let(Scope {
+                    unsafe_pattern: Nest { unsafe_patterns },
+                    unsafe_body: self.term()?,
+                })
+            },
+            tag => return Err(DecodeError::InvalidTag { kind: "term", tag }),
+        };
+
+        Ok(RcTerm::from(term))
+    }
+}
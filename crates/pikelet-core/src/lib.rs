@@ -1,4 +1,8 @@
 //! The syntax of the language
 
+pub mod interface;
+pub mod limits;
 pub mod nbe;
+pub mod run;
 pub mod syntax;
+pub mod zipper;
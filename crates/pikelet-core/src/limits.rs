@@ -0,0 +1,50 @@
+//! Limits on the size of terms
+//!
+//! These guard against adversarial or accidentally exponential terms, which
+//! would otherwise exhaust the stack or the heap before producing an error.
+
+use failure::Fail;
+
+/// Configurable limits that are enforced during parsing and normalization
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum depth that terms may be nested to
+    ///
+    /// Parameters each count as a level of nesting, as they bind variables in
+    /// nested scopes. Arguments and items count toward `max_size` instead.
+    pub max_depth: usize,
+    /// The maximum depth of recursion when normalizing a term
+    ///
+    /// Normalization can recurse much more deeply than the term being
+    /// normalized is nested, for example when unfolding definitions, so this
+    /// is kept separate from `max_depth`.
+    pub max_eval_depth: usize,
+    /// The maximum number of terms that may be evaluated when normalizing a
+    /// term, which also bounds the size of the resulting normal form, and the
+    /// number of terms that may be parsed
+    pub max_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_depth: 256,
+            max_eval_depth: 512,
+            max_size: 1_000_000,
+        }
+    }
+}
+
+/// A limit that was exceeded
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Fail)]
+pub enum LimitExceeded {
+    #[fail(display = "exceeded the maximum depth of {}", max_depth)]
+    Depth { max_depth: usize },
+    #[fail(
+        display = "exceeded the maximum evaluation depth of {}",
+        max_eval_depth
+    )]
+    EvalDepth { max_eval_depth: usize },
+    #[fail(display = "exceeded the maximum size of {}", max_size)]
+    Size { max_size: usize },
+}
@@ -1,23 +1,28 @@
 use failure::Fail;
 use moniker::{Binder, Embed, FreeVar, Nest, Scope, Var};
 
+use crate::limits::{LimitExceeded, Limits};
 use crate::syntax::core::{Pattern, RcPattern, RcTerm, Term};
 use crate::syntax::domain::{Head, Neutral, RcNeutral, RcValue, Value};
 use crate::syntax::Import;
 
 /// An error produced during normalization
-///
-/// If a term has been successfully type checked prior to evaluation or
-/// normalization, then this error should never be produced.
 #[derive(Debug, Clone, PartialEq, Fail)]
-#[fail(display = "{}", message)]
-pub struct NbeError {
-    pub message: String,
+pub enum NbeError {
+    /// An unexpected term was encountered
+    ///
+    /// If a term has been successfully type checked prior to evaluation or
+    /// normalization, then this error should never be produced.
+    #[fail(display = "{}", message)]
+    Internal { message: String },
+    /// The term could not be normalized within the limits of the environment
+    #[fail(display = "term too large: {}", _0)]
+    TooLarge(#[cause] LimitExceeded),
 }
 
 impl NbeError {
     pub fn new(message: impl Into<String>) -> NbeError {
-        NbeError {
+        NbeError::Internal {
             message: message.into(),
         }
     }
@@ -27,13 +32,92 @@ impl NbeError {
 pub trait Env {
     fn get_import(&self, name: &str) -> Option<&Import>;
     fn get_definition(&self, free_var: &FreeVar<String>) -> Option<&RcTerm>;
+
+    /// Look up the normal form of a definition, if it has already been
+    /// computed by a previous call to `cache_definition_nf`
+    fn get_definition_nf(&self, _free_var: &FreeVar<String>) -> Option<RcValue> {
+        None
+    }
+
+    /// Remember the normal form of a definition, so that it does not need to
+    /// be normalized again the next time it is used
+    fn cache_definition_nf(&self, _free_var: &FreeVar<String>, _value: &RcValue) {}
+
+    /// The limits to enforce during normalization
+    fn limits(&self) -> Limits {
+        Limits::default()
+    }
+}
+
+/// Keeps track of how much of the limits we have used up so far
+struct Budget {
+    limits: Limits,
+    depth: usize,
+    size: usize,
+}
+
+impl Budget {
+    fn new(env: &dyn Env) -> Budget {
+        Budget {
+            limits: env.limits(),
+            depth: 0,
+            size: 0,
+        }
+    }
 }
 
 /// Reduce a term to its normal form
 pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
+    nf_term_budgeted(env, term, &mut Budget::new(env))
+}
+
+fn nf_term_budgeted(
+    env: &dyn Env,
+    term: &RcTerm,
+    budget: &mut Budget,
+) -> Result<RcValue, NbeError> {
+    if budget.depth >= budget.limits.max_eval_depth {
+        let max_eval_depth = budget.limits.max_eval_depth;
+        return Err(NbeError::TooLarge(LimitExceeded::EvalDepth { max_eval_depth }));
+    }
+    if budget.size >= budget.limits.max_size {
+        let max_size = budget.limits.max_size;
+        return Err(NbeError::TooLarge(LimitExceeded::Size { max_size }));
+    }
+
+    budget.depth += 1;
+    budget.size += 1;
+    let value = nf_term_step(env, term, budget);
+    budget.depth -= 1;
+
+    value
+}
+
+/// Reduce a definition to its normal form, reusing the cached normal form if
+/// it has already been computed
+fn nf_definition(
+    env: &dyn Env,
+    free_var: &FreeVar<String>,
+    budget: &mut Budget,
+) -> Result<Option<RcValue>, NbeError> {
+    if let Some(value) = env.get_definition_nf(free_var) {
+        return Ok(Some(value));
+    }
+
+    match env.get_definition(free_var) {
+        Some(term) => {
+            let value = nf_term_budgeted(env, term, budget)?;
+            env.cache_definition_nf(free_var, &value);
+            Ok(Some(value))
+        },
+        None => Ok(None),
+    }
+}
+
+fn nf_term_step(env: &dyn Env, term: &RcTerm, budget: &mut Budget) -> Result<RcValue, NbeError> {
     match *term.inner {
         // E-ANN
-        Term::Ann(ref expr, _) => nf_term(env, expr),
+        Term::Ann(ref expr, _) => nf_term_budgeted(env, expr, budget),
 
         // E-TYPE
         Term::Universe(level) => Ok(RcValue::from(Value::Universe(level))),
@@ -42,9 +126,8 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
 
         // E-VAR, E-VAR-DEF
         Term::Var(ref var, shift) => match *var {
-            Var::Free(ref name) => match env.get_definition(name) {
-                Some(term) => {
-                    let mut value = nf_term(env, term)?;
+            Var::Free(ref name) => match nf_definition(env, name, budget)? {
+                Some(mut value) => {
                     value.shift_universes(shift);
                     Ok(value)
                 },
@@ -58,16 +141,16 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
         },
 
         Term::Import(ref name) => match env.get_import(name) {
-            Some(&Import::Term(ref term)) => nf_term(env, term),
+            Some(&Import::Term(ref term)) => nf_term_budgeted(env, term, budget),
             Some(&Import::Prim(ref interpretation)) => match interpretation(&[]) {
                 Some(value) => Ok(value),
                 None => Ok(RcValue::from(Value::from(Neutral::Head(Head::Import(
                     name.clone(),
                 ))))),
             },
-            None => Ok(RcValue::from(Value::from(Neutral::Head(Head::Import(
-                name.clone(),
-            ))))),
+            Some(&Import::Opaque) | None => Ok(RcValue::from(Value::from(Neutral::Head(
+                Head::Import(name.clone()),
+            )))),
         },
 
         // E-PI
@@ -75,8 +158,8 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
             let ((name, Embed(ann)), body) = scope.clone().unbind();
 
             Ok(RcValue::from(Value::FunType(Scope::new(
-                (name, Embed(nf_term(env, &ann)?)),
-                nf_term(env, &body)?,
+                (name, Embed(nf_term_budgeted(env, &ann, budget)?)),
+                nf_term_budgeted(env, &body, budget)?,
             ))))
         },
 
@@ -85,21 +168,21 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
             let ((name, Embed(ann)), body) = scope.clone().unbind();
 
             Ok(RcValue::from(Value::FunIntro(Scope::new(
-                (name, Embed(nf_term(env, &ann)?)),
-                nf_term(env, &body)?,
+                (name, Embed(nf_term_budgeted(env, &ann, budget)?)),
+                nf_term_budgeted(env, &body, budget)?,
             ))))
         },
 
         // E-APP
         Term::FunApp(ref head, ref arg) => {
-            match *nf_term(env, head)?.inner {
+            match *nf_term_budgeted(env, head, budget)?.inner {
                 Value::FunIntro(ref scope) => {
                     // FIXME: do a local unbind here
                     let ((Binder(free_var), Embed(_)), body) = scope.clone().unbind();
-                    nf_term(env, &body.substs(&[(free_var, arg.clone())]))
+                    nf_term_budgeted(env, &body.substs(&[(free_var, arg.clone())]), budget)
                 },
                 Value::Neutral(ref neutral, ref spine) => {
-                    let arg = nf_term(env, arg)?;
+                    let arg = nf_term_budgeted(env, arg, budget)?;
                     let mut spine = spine.clone();
 
                     match *neutral.inner {
@@ -117,7 +200,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
                                         None => {},
                                     }
                                 },
-                                None => {},
+                                Some(&Import::Opaque) | None => {},
                             }
                         },
                         Neutral::Head(Head::Var(..))
@@ -137,11 +220,11 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
             let mut mappings = Vec::with_capacity(bindings.unsafe_patterns.len());
 
             for (Binder(free_var), Embed(term)) in bindings.unnest() {
-                let value = nf_term(env, &term.substs(&mappings))?;
+                let value = nf_term_budgeted(env, &term.substs(&mappings), budget)?;
                 mappings.push((free_var, RcTerm::from(&*value.inner)));
             }
 
-            nf_term(env, &body.substs(&mappings))
+            nf_term_budgeted(env, &body.substs(&mappings), budget)
         },
 
         // E-RECORD-TYPE, E-EMPTY-RECORD-TYPE
@@ -152,7 +235,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
                     .unnest()
                     .into_iter()
                     .map(|(label, binder, Embed(ann))| {
-                        Ok((label, binder, Embed(nf_term(env, &ann)?)))
+                        Ok((label, binder, Embed(nf_term_budgeted(env, &ann, budget)?)))
                     })
                     .collect::<Result<_, _>>()?,
             );
@@ -164,7 +247,9 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
         Term::RecordIntro(ref fields) => {
             let fields = fields
                 .iter()
-                .map(|&(ref label, ref term)| Ok((label.clone(), nf_term(env, &term)?)))
+                .map(|&(ref label, ref term)| {
+                    Ok((label.clone(), nf_term_budgeted(env, &term, budget)?))
+                })
                 .collect::<Result<_, _>>()?;
 
             Ok(RcValue::from(Value::RecordIntro(fields)))
@@ -172,7 +257,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
 
         // E-PROJ
         Term::RecordProj(ref expr, ref label, shift) => {
-            match *nf_term(env, expr)? {
+            match *nf_term_budgeted(env, expr, budget)? {
                 Value::Neutral(ref neutral, ref spine) => {
                     return Ok(RcValue::from(Value::Neutral(
                         RcNeutral::from(Neutral::RecordProj(neutral.clone(), label.clone(), shift)),
@@ -197,7 +282,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
 
         // E-CASE
         Term::Case(ref head, ref clauses) => {
-            let head = nf_term(env, head)?;
+            let head = nf_term_budgeted(env, head, budget)?;
 
             if let Value::Neutral(ref neutral, ref spine) = *head {
                 Ok(RcValue::from(Value::Neutral(
@@ -207,7 +292,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
                             .iter()
                             .map(|clause| {
                                 let (pattern, body) = clause.clone().unbind();
-                                Ok(Scope::new(pattern, nf_term(env, &body)?))
+                                Ok(Scope::new(pattern, nf_term_budgeted(env, &body, budget)?))
                             })
                             .collect::<Result<_, _>>()?,
                     )),
@@ -216,12 +301,12 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
             } else {
                 for clause in clauses {
                     let (pattern, body) = clause.clone().unbind();
-                    if let Some(mappings) = match_value(env, &pattern, &head)? {
+                    if let Some(mappings) = match_value_budgeted(env, &pattern, &head, budget)? {
                         let mappings = mappings
                             .into_iter()
                             .map(|(free_var, value)| (free_var, RcTerm::from(&*value.inner)))
                             .collect::<Vec<_>>();
-                        return nf_term(env, &body.substs(&mappings));
+                        return nf_term_budgeted(env, &body.substs(&mappings), budget);
                     }
                 }
                 Err(NbeError::new("no patterns applicable"))
@@ -232,7 +317,7 @@ pub fn nf_term(env: &dyn Env, term: &RcTerm) -> Result<RcValue, NbeError> {
         Term::ArrayIntro(ref elems) => Ok(RcValue::from(Value::ArrayIntro(
             elems
                 .iter()
-                .map(|elem| nf_term(env, elem))
+                .map(|elem| nf_term_budgeted(env, elem, budget))
                 .collect::<Result<_, _>>()?,
         ))),
     }
@@ -244,16 +329,24 @@ pub fn match_value(
     env: &dyn Env,
     pattern: &RcPattern,
     value: &RcValue,
+) -> Result<Option<Vec<(FreeVar<String>, RcValue)>>, NbeError> {
+    match_value_budgeted(env, pattern, value, &mut Budget::new(env))
+}
+
+fn match_value_budgeted(
+    env: &dyn Env,
+    pattern: &RcPattern,
+    value: &RcValue,
+    budget: &mut Budget,
 ) -> Result<Option<Vec<(FreeVar<String>, RcValue)>>, NbeError> {
     match (&*pattern.inner, &*value.inner) {
         (&Pattern::Binder(Binder(ref free_var)), _) => {
             Ok(Some(vec![(free_var.clone(), value.clone())]))
         },
         (&Pattern::Var(Embed(Var::Free(ref free_var)), _), _) => {
-            match env.get_definition(free_var).map(|term| nf_term(env, term)) {
-                Some(Ok(ref term)) if term == value => Ok(Some(vec![])),
-                Some(Ok(_)) | None => Ok(None),
-                Some(Err(err)) => Err(err),
+            match nf_definition(env, free_var, budget)? {
+                Some(ref term) if term == value => Ok(Some(vec![])),
+                Some(_) | None => Ok(None),
             }
         },
         (&Pattern::Literal(ref pattern_lit), &Value::Literal(ref value_lit))
@@ -0,0 +1,74 @@
+//! An interpreter for `IO` actions
+//!
+//! `IO` actions are built out of the primitive imports defined here. These
+//! have no interpretation during normalization, so an action normalizes to a
+//! neutral term that describes what should be done, which can then be
+//! carried out by `run_io`.
+
+use failure::Fail;
+use std::io::Write;
+
+use crate::nbe::{self, Env, NbeError};
+use crate::syntax::core::{RcTerm, Term};
+use crate::syntax::domain::{Head, RcValue, Value};
+use crate::syntax::Literal;
+
+/// `(a : Type) -> (b : Type) -> IO a -> (a -> IO b) -> IO b`
+pub const BIND: &str = "prim/io/bind";
+/// `String -> IO Record {}`
+pub const PRINT: &str = "prim/io/print";
+/// `(a : Type) -> a -> IO a`
+pub const PURE: &str = "prim/io/pure";
+
+/// An error produced while running an `IO` action
+#[derive(Debug, Fail)]
+pub enum RunError {
+    #[fail(display = "{}", _0)]
+    Nbe(#[cause] NbeError),
+    #[fail(display = "failed to write output: {}", _0)]
+    Output(#[cause] std::io::Error),
+    /// The action could not be reduced to one of the primitive actions, for
+    /// example because it depends on a variable that has no definition
+    #[fail(display = "unable to run a stuck `IO` action")]
+    Stuck,
+}
+
+impl From<NbeError> for RunError {
+    fn from(src: NbeError) -> RunError {
+        RunError::Nbe(src)
+    }
+}
+
+impl From<std::io::Error> for RunError {
+    fn from(src: std::io::Error) -> RunError {
+        RunError::Output(src)
+    }
+}
+
+/// Run a normalized `IO` action, writing any output to `writer`, and
+/// returning the result of the action
+pub fn run_io(
+    env: &dyn Env,
+    action: &RcValue,
+    writer: &mut dyn Write,
+) -> Result<RcValue, RunError> {
+    match action.head_app() {
+        Some((&Head::Import(ref name), spine)) => match (name.as_str(), &spine[..]) {
+            (PURE, &[_, ref value]) => Ok(value.clone()),
+            (PRINT, &[ref value]) => match **value {
+                Value::Literal(Literal::String(ref value)) => {
+                    writeln!(writer, "{}", value)?;
+                    Ok(RcValue::from(Value::RecordIntro(Vec::new())))
+                },
+                _ => Err(RunError::Stuck),
+            },
+            (BIND, &[_, _, ref action, ref next]) => {
+                let value = run_io(env, action, writer)?;
+                let next = RcTerm::from(Term::FunApp(RcTerm::from(&**next), RcTerm::from(&*value)));
+                run_io(env, &nbe::nf_term(env, &next)?, writer)
+            },
+            _ => Err(RunError::Stuck),
+        },
+        Some(_) | None => Err(RunError::Stuck),
+    }
+}
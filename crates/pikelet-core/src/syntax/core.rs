@@ -293,6 +293,15 @@ pub struct RcTerm {
 }
 
 impl RcTerm {
+    /// The number of nodes in the term, not including the patterns of case
+    /// clauses
+    ///
+    /// This is useful for getting a rough idea of how much memory a term uses.
+    pub fn node_count(&self) -> usize {
+        let children = crate::zipper::children(self);
+        1 + children.iter().map(RcTerm::node_count).sum::<usize>()
+    }
+
     pub fn substs(&self, mappings: &[(FreeVar<String>, RcTerm)]) -> RcTerm {
         match *self.inner {
             Term::Ann(ref term, ref ty) => {
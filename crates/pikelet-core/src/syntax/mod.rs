@@ -17,6 +17,9 @@ pub const PRETTY_FALLBACK_WIDTH: usize = 1_000_000;
 #[derive(Clone)]
 pub enum Import {
     Term(core::RcTerm),
+    /// An import whose type is known, but whose definition is not available,
+    /// for example when it was loaded from a signature-only interface
+    Opaque,
     Prim(for<'a> fn(&'a [domain::RcValue]) -> Option<domain::RcValue>),
 }
 
@@ -24,6 +27,7 @@ impl fmt::Debug for Import {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Import::Term(ref term) => f.debug_tuple("Term").field(term).finish(),
+            Import::Opaque => write!(f, "Opaque"),
             Import::Prim(_) => f.debug_tuple("Prim").field(&"|params| { .. }").finish(),
         }
     }
@@ -0,0 +1,250 @@
+//! Zippers for navigating and editing core terms
+//!
+//! A zipper keeps track of a _focused_ subterm, along with the path back up
+//! to the root of the term. This allows interactive tools, like steppers or
+//! structured editors, to move around a term and replace parts of it without
+//! having to reimplement the traversal each time.
+//!
+//! Note that the subterms found underneath binders are in the locally
+//! nameless representation - that is, any variables bound by the enclosing
+//! scopes will appear as `Var::Bound`. This means that subterms can be
+//! replaced and zipped back up without needing to rebind any variables.
+
+use moniker::{Embed, Nest, Scope};
+
+use crate::syntax::core::{RcTerm, Term};
+
+/// A step on the path back up to the root of a term
+#[derive(Debug, Clone, PartialEq)]
+struct Crumb {
+    /// The term that we descended from
+    parent: RcTerm,
+    /// The children of the parent term, excluding the focus
+    children: Vec<RcTerm>,
+    /// The position of the focus in the children of the parent
+    index: usize,
+}
+
+/// A core term, with the focus on one of its subterms
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zipper {
+    focus: RcTerm,
+    path: Vec<Crumb>,
+}
+
+impl Zipper {
+    /// Create a zipper, focused on the root of the given term
+    pub fn new(term: RcTerm) -> Zipper {
+        Zipper {
+            focus: term,
+            path: Vec::new(),
+        }
+    }
+
+    /// The currently focused subterm
+    pub fn focus(&self) -> &RcTerm {
+        &self.focus
+    }
+
+    /// The number of steps between the focus and the root of the term
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The position of the focus among its siblings, or `None` if we are at
+    /// the root of the term
+    pub fn index(&self) -> Option<usize> {
+        self.path.last().map(|crumb| crumb.index)
+    }
+
+    /// Replace the focused subterm, returning the old one
+    pub fn replace(&mut self, term: RcTerm) -> RcTerm {
+        std::mem::replace(&mut self.focus, term)
+    }
+
+    /// Move the focus to the first child of the current focus, returning
+    /// `false` if it has no children
+    pub fn down(&mut self) -> bool {
+        self.down_nth(0)
+    }
+
+    /// Move the focus to the `n`th child of the current focus, returning
+    /// `false` if no such child exists
+    pub fn down_nth(&mut self, n: usize) -> bool {
+        let mut children = children(&self.focus);
+        if n >= children.len() {
+            return false;
+        }
+
+        let focus = children.remove(n);
+        let parent = std::mem::replace(&mut self.focus, focus);
+        self.path.push(Crumb {
+            parent,
+            children,
+            index: n,
+        });
+
+        true
+    }
+
+    /// Move the focus to the parent of the current focus, returning `false`
+    /// if we are already at the root of the term
+    pub fn up(&mut self) -> bool {
+        match self.path.pop() {
+            None => false,
+            Some(crumb) => {
+                let Crumb {
+                    parent,
+                    mut children,
+                    index,
+                } = crumb;
+                let focus = std::mem::replace(&mut self.focus, parent.clone());
+                children.insert(index, focus);
+                self.focus = replace_children(&parent, children);
+
+                true
+            },
+        }
+    }
+
+    /// Move the focus to the previous sibling of the current focus,
+    /// returning `false` if there is none
+    pub fn left(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some(ref mut crumb) if crumb.index > 0 => {
+                let index = crumb.index - 1;
+                let focus = crumb.children.remove(index);
+                let old_focus = std::mem::replace(&mut self.focus, focus);
+                crumb.children.insert(index, old_focus);
+                crumb.index = index;
+
+                true
+            },
+            Some(_) | None => false,
+        }
+    }
+
+    /// Move the focus to the next sibling of the current focus, returning
+    /// `false` if there is none
+    pub fn right(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some(ref mut crumb) if crumb.index < crumb.children.len() => {
+                let index = crumb.index;
+                let focus = crumb.children.remove(index);
+                let old_focus = std::mem::replace(&mut self.focus, focus);
+                crumb.children.insert(index, old_focus);
+                crumb.index = index + 1;
+
+                true
+            },
+            Some(_) | None => false,
+        }
+    }
+
+    /// Move the focus back to the root of the term
+    pub fn top(&mut self) {
+        while self.up() {}
+    }
+
+    /// Zip the term back up, returning the root of the term
+    pub fn into_term(mut self) -> RcTerm {
+        self.top();
+        self.focus
+    }
+}
+
+/// The immediate subterms of a term, in the order that they appear in the
+/// source code
+///
+/// The patterns of case clauses are not included.
+pub fn children(term: &RcTerm) -> Vec<RcTerm> {
+    match *term.inner {
+        Term::Universe(_) | Term::Literal(_) | Term::Var(_, _) | Term::Import(_) => Vec::new(),
+        Term::Ann(ref term, ref ty) => vec![term.clone(), ty.clone()],
+        Term::FunType(ref scope) | Term::FunIntro(ref scope) => {
+            let (_, Embed(ref ann)) = scope.unsafe_pattern;
+            vec![ann.clone(), scope.unsafe_body.clone()]
+        },
+        Term::FunApp(ref head, ref arg) => vec![head.clone(), arg.clone()],
+        Term::RecordType(ref scope) => (scope.unsafe_pattern.unsafe_patterns.iter())
+            .map(|&(_, _, Embed(ref ann))| ann.clone())
+            .collect(),
+        Term::RecordIntro(ref fields) => fields.iter().map(|&(_, ref term)| term.clone()).collect(),
+        Term::RecordProj(ref expr, _, _) => vec![expr.clone()],
+        Term::Case(ref head, ref clauses) => std::iter::once(head.clone())
+            .chain(clauses.iter().map(|clause| clause.unsafe_body.clone()))
+            .collect(),
+        Term::ArrayIntro(ref elems) => elems.clone(),
+        Term::Let(ref scope) => (scope.unsafe_pattern.unsafe_patterns.iter())
+            .map(|&(_, Embed(ref value))| value.clone())
+            .chain(std::iter::once(scope.unsafe_body.clone()))
+            .collect(),
+    }
+}
+
+/// Rebuild a term, replacing its immediate subterms with the given ones
+///
+/// The number of children must match the number returned by `children`.
+fn replace_children(term: &RcTerm, children: Vec<RcTerm>) -> RcTerm {
+    let mut children = children.into_iter();
+    let mut next = || children.next().expect("not enough children");
+
+    let term = match *term.inner {
+        Term::Universe(_) | Term::Literal(_) | Term::Var(_, _) | Term::Import(_) => {
+            return term.clone();
+        },
+        Term::Ann(_, _) => Term::Ann(next(), next()),
+        Term::FunType(ref scope) | Term::FunIntro(ref scope) => {
+            let (ref name, _) = scope.unsafe_pattern;
+            let scope = Scope {
+                unsafe_pattern: (name.clone(), Embed(next())),
+                unsafe_body: next(),
+            };
+            match *term.inner {
+                Term::FunType(_) => Term::FunType(scope),
+                _ => Term::FunIntro(scope),
+            }
+        },
+        Term::FunApp(_, _) => Term::FunApp(next(), next()),
+        Term::RecordType(ref scope) => Term::RecordType(Scope {
+            unsafe_pattern: Nest {
+                unsafe_patterns: (scope.unsafe_pattern.unsafe_patterns.iter())
+                    .map(|&(ref label, ref binder, _)| {
+                        (label.clone(), binder.clone(), Embed(next()))
+                    })
+                    .collect(),
+            },
+            unsafe_body: (),
+        }),
+        Term::RecordIntro(ref fields) => Term::RecordIntro(
+            fields
+                .iter()
+                .map(|&(ref label, _)| (label.clone(), next()))
+                .collect(),
+        ),
+        Term::RecordProj(_, ref label, shift) => Term::RecordProj(next(), label.clone(), shift),
+        Term::Case(_, ref clauses) => {
+            let head = next();
+            let clauses = clauses
+                .iter()
+                .map(|clause| Scope {
+                    unsafe_pattern: clause.unsafe_pattern.clone(),
+                    unsafe_body: next(),
+                })
+                .collect();
+            Term::Case(head, clauses)
+        },
+        Term::ArrayIntro(ref elems) => Term::ArrayIntro(elems.iter().map(|_| next()).collect()),
+        Term::Let(ref scope) => {
+            let unsafe_patterns = (scope.unsafe_pattern.unsafe_patterns.iter())
+                .map(|&(ref binder, _)| (binder.clone(), Embed(next())))
+                .collect();
+            Term::Let(Scope {
+                unsafe_pattern: Nest { unsafe_patterns },
+                unsafe_body: next(),
+            })
+        },
+    };
+
+    RcTerm::from(term)
+}
@@ -0,0 +1,60 @@
+//! Compute a digest of the sources of the type checker
+//!
+//! This is used as part of the key of the interface cache, so that changes to
+//! how modules are checked invalidate any interfaces that were cached by an
+//! earlier build, even if the package version was not bumped.
+
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The directories, relative to the workspace's `crates` directory, whose
+/// contents can affect the result of checking a module
+const SOURCE_DIRS: &[&str] = &[
+    "pikelet-core/src",
+    "pikelet-concrete/src",
+    "pikelet-driver/src",
+];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let crates_dir = manifest_dir.parent().unwrap();
+
+    let mut hasher = Sha256::new();
+    for source_dir in SOURCE_DIRS {
+        let dir = crates_dir.join(source_dir);
+        println!("cargo:rerun-if-changed={}", dir.display());
+
+        let mut paths = Vec::new();
+        collect_files(&dir, &mut paths).unwrap();
+        paths.sort();
+
+        for path in paths {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let relative_path = path.strip_prefix(crates_dir).unwrap();
+            hasher.input(relative_path.to_string_lossy().as_bytes());
+            hasher.input(fs::read(&path).unwrap());
+        }
+    }
+
+    let digest = hasher
+        .result()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    println!("cargo:rustc-env=PIKELET_CHECKER_DIGEST={}", digest);
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
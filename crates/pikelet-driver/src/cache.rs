@@ -0,0 +1,98 @@
+//! A content-addressed cache of checked module interfaces
+//!
+//! Interfaces are stored under the digest of everything that could affect
+//! the result of checking a module: the source code of the module, the
+//! digests of the interfaces it depends on, the limits used when checking
+//! it, and a digest of the sources of the type checker itself (computed by
+//! the build script). If none of these have changed then the module does not
+//! need to be checked again.
+
+use sha2::{Digest as _, Sha256};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use pikelet_core::interface;
+use pikelet_core::limits::Limits;
+
+/// The digest of a module and its dependencies
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Digest([u8; 32]);
+
+impl Digest {
+    /// Compute the digest of a module, given its source code, the limits it
+    /// will be checked with, and the digests of its dependencies
+    pub fn new(src: &str, limits: Limits, dependencies: &[Digest]) -> Digest {
+        let mut hasher = Sha256::new();
+
+        hasher.input(env!("PIKELET_CHECKER_DIGEST").as_bytes());
+        hasher.input(interface::VERSION.to_le_bytes());
+        hasher.input((limits.max_depth as u64).to_le_bytes());
+        hasher.input((limits.max_eval_depth as u64).to_le_bytes());
+        hasher.input((limits.max_size as u64).to_le_bytes());
+        hasher.input((dependencies.len() as u64).to_le_bytes());
+        for dependency in dependencies {
+            hasher.input(dependency.0);
+        }
+        hasher.input(src.as_bytes());
+
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&hasher.result());
+        Digest(digest)
+    }
+}
+
+/// The digest of the modules that are loaded by `Driver::with_prelude`
+pub fn prelude_digest(limits: Limits) -> Digest {
+    let prim = Digest::new(pikelet_library::PRIM, limits, &[]);
+    Digest::new(pikelet_library::PRELUDE, limits, &[prim])
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory of cached interfaces, indexed by digest
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Cache {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, digest: &Digest) -> PathBuf {
+        self.dir
+            .join(digest.to_string())
+            .with_extension(interface::EXTENSION)
+    }
+
+    /// Load the interface stored under the given digest, if it exists
+    pub fn load(&self, digest: &Digest) -> Option<Vec<u8>> {
+        fs::read(self.path(digest)).ok()
+    }
+
+    /// Store an encoded interface under the given digest
+    pub fn store(&self, digest: &Digest, bytes: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Write to a temporary file first, so that concurrent readers never
+        // see a partially written interface
+        let path = self.path(digest);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, &path)
+    }
+}
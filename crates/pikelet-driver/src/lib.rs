@@ -127,14 +127,58 @@
 
 use codespan::CodeMap;
 pub use codespan::FileName;
-pub use codespan_reporting::{termcolor, ColorArg, Diagnostic};
+pub use codespan_reporting::{termcolor, ColorArg, Diagnostic, Severity};
+use std::fmt;
 use std::io;
+use std::rc::Rc;
 
 use pikelet_concrete::desugar::{Desugar, DesugarEnv};
-use pikelet_concrete::elaborate::Context;
+pub use pikelet_concrete::elaborate::TraceStep;
+use pikelet_concrete::elaborate::{Context, Trace};
 use pikelet_concrete::resugar::Resugar;
-use pikelet_concrete::syntax::raw;
-use pikelet_core::syntax::{core, domain, Import};
+use pikelet_concrete::syntax::{concrete, raw};
+pub use pikelet_core::interface::{self, Interface};
+pub use pikelet_core::limits::Limits;
+use pikelet_core::syntax::{core, domain, Import, Level};
+
+pub mod cache;
+pub mod progress;
+
+use crate::progress::Progress;
+
+/// A summary of the memory used by a driver, for tracking down leaks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The entries stored in the type checking context
+    pub context: pikelet_concrete::elaborate::MemoryUsage,
+    /// The number of source files in the codemap
+    pub source_files: usize,
+    /// The total size of the source files in the codemap
+    pub source_bytes: usize,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "imports:      {}", self.context.imports)?;
+        writeln!(f, "declarations: {}", self.context.declarations)?;
+        writeln!(
+            f,
+            "definitions:  {} ({} normalized)",
+            self.context.definitions, self.context.cached_nfs,
+        )?;
+        writeln!(
+            f,
+            "core terms:   {} nodes (~{} bytes)",
+            self.context.term_nodes,
+            self.context.term_bytes(),
+        )?;
+        write!(
+            f,
+            "source code:  {} files ({} bytes)",
+            self.source_files, self.source_bytes,
+        )
+    }
+}
 
 /// An environment that keeps track of the state of a Pikelet program during
 /// compilation or interactive sessions
@@ -184,6 +228,26 @@ impl Driver {
         pikelet
     }
 
+    /// Set the limits on the size of terms that will be parsed and normalized
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.context.set_limits(limits);
+    }
+
+    /// Enable or disable tracing of the elaboration steps taken by the type
+    /// checker, recording steps up to the given depth
+    pub fn set_trace(&mut self, max_depth: Option<usize>) {
+        self.context
+            .set_trace(max_depth.map(|max_depth| Rc::new(Trace::new(max_depth))));
+    }
+
+    /// Take the elaboration steps that have been traced since this was last called
+    pub fn take_trace(&self) -> Vec<TraceStep> {
+        match self.context.trace() {
+            Some(trace) => trace.take_steps(),
+            None => Vec::new(),
+        }
+    }
+
     /// Add a binding to the driver's top-level environment
     pub fn add_binding(&mut self, name: &str, term: core::RcTerm, ann: domain::RcType) {
         let fv = self.desugar_env.on_binding(&name);
@@ -191,6 +255,29 @@ impl Driver {
         self.context.insert_definition(fv.clone(), term.clone());
     }
 
+    /// The names that are currently in scope, along with their types, sorted
+    /// by name
+    pub fn bindings(&self) -> Vec<(String, concrete::Term)> {
+        let mut bindings = (self.desugar_env.locals())
+            .filter_map(|(name, free_var)| {
+                let ty = self.context.get_declaration(free_var)?;
+                Some((name.to_owned(), self.resugar(ty)))
+            })
+            .collect::<Vec<_>>();
+
+        bindings.sort_by(|(name0, _), (name1, _)| name0.cmp(name1));
+        bindings
+    }
+
+    /// Summarize the memory used by the driver
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            context: self.context.memory_usage(),
+            source_files: self.code_map.iter().count(),
+            source_bytes: self.code_map.iter().map(|file| file.src().len()).sum(),
+        }
+    }
+
     /// Register a file with the driver
     pub fn register_file(
         &mut self,
@@ -205,33 +292,173 @@ impl Driver {
         Ok(())
     }
 
-    /// Infer the type of a file
-    pub fn infer_file(
+    /// Parse and desugar a file
+    fn desugar_file(
         &mut self,
         name: FileName,
         src: String,
-    ) -> Result<(core::RcTerm, domain::RcType), Vec<Diagnostic>> {
+    ) -> Result<raw::RcTerm, Vec<Diagnostic>> {
         let file_map = self.code_map.add_filemap(name, src);
         // TODO: follow import paths
-        let (concrete_term, _import_paths, errors) = pikelet_concrete::parse::term(&file_map);
+        let limits = self.context.limits();
+        let (concrete_term, _import_paths, errors) =
+            pikelet_concrete::parse::term_with_limits(&file_map, limits);
         if !errors.is_empty() {
             return Err(errors.iter().map(|error| error.to_diagnostic()).collect());
         }
-        let raw_term = self.desugar(&concrete_term)?;
+        self.desugar(&concrete_term)
+    }
+
+    /// Infer the type of a file
+    pub fn infer_file(
+        &mut self,
+        name: FileName,
+        src: String,
+    ) -> Result<(core::RcTerm, domain::RcType), Vec<Diagnostic>> {
+        let raw_term = self.desugar_file(name, src)?;
         self.infer_term(&raw_term)
     }
 
+    /// Infer the universe level of a file containing a type
+    pub fn infer_file_universe(
+        &mut self,
+        name: FileName,
+        src: String,
+    ) -> Result<(core::RcTerm, Level), Vec<Diagnostic>> {
+        let raw_term = self.desugar_file(name, src)?;
+        pikelet_concrete::elaborate::infer_universe(&self.context, &raw_term)
+            .map_err(|err| vec![err.to_diagnostic()])
+    }
+
+    /// Check the contents of a file, returning any warnings and errors that
+    /// were encountered along the way
+    pub fn check_file(&mut self, name: FileName, src: String) -> Vec<Diagnostic> {
+        self.check_file_interface(name, src, false).1
+    }
+
+    /// Check the contents of a file, returning its interface if it was
+    /// successfully checked, along with any warnings and errors that were
+    /// encountered along the way
+    pub fn check_file_interface(
+        &mut self,
+        name: FileName,
+        src: String,
+        include_body: bool,
+    ) -> (Option<Interface>, Vec<Diagnostic>) {
+        self.check_file_interface_with_progress(name, src, include_body, &mut ())
+    }
+
+    /// Check the contents of a file like `check_file_interface`, reporting
+    /// each stage of checking to `progress`
+    pub fn check_file_interface_with_progress(
+        &mut self,
+        name: FileName,
+        src: String,
+        include_body: bool,
+        progress: &mut dyn Progress,
+    ) -> (Option<Interface>, Vec<Diagnostic>) {
+        progress.report("parsing", 0.0);
+        let file_map = self.code_map.add_filemap(name, src);
+        let limits = self.context.limits();
+        let (concrete_term, _import_paths, errors) =
+            pikelet_concrete::parse::term_with_limits(&file_map, limits);
+        if !errors.is_empty() {
+            progress.report("done", 1.0);
+            return (
+                None,
+                errors.iter().map(|error| error.to_diagnostic()).collect(),
+            );
+        }
+
+        progress.report("linting", 0.25);
+        let mut diagnostics = self.lint(&concrete_term);
+        progress.report("desugaring", 0.5);
+        let result = self.desugar(&concrete_term).and_then(|raw_term| {
+            progress.report("elaborating", 0.75);
+            self.infer_term(&raw_term)
+        });
+        progress.report("done", 1.0);
+
+        match result {
+            Ok((term, ty)) => {
+                let interface = Interface {
+                    ty: core::RcTerm::from(&*ty),
+                    body: if include_body { Some(term) } else { None },
+                };
+                (Some(interface), diagnostics)
+            },
+            Err(errors) => {
+                diagnostics.extend(errors);
+                (None, diagnostics)
+            },
+        }
+    }
+
+    /// Register a previously checked interface with the driver, without
+    /// having to check the source code of the module again
+    ///
+    /// Returns an error if an import has already been registered at `path`,
+    /// as existing terms may still refer to it.
+    pub fn register_interface(
+        &mut self,
+        path: String,
+        bytes: &[u8],
+    ) -> Result<(), Vec<Diagnostic>> {
+        use pikelet_concrete::elaborate::TypeError;
+
+        if self.context.get_import(&path).is_some() {
+            let message = format!("an import has already been registered at `{}`", path);
+            return Err(vec![Diagnostic::new_error(message)]);
+        }
+
+        let mappings = self.context.mappings();
+        let limits = self.context.limits();
+        let interface = Interface::decode(bytes, limits, |name| mappings.get(name).cloned())
+            .map_err(|err| vec![Diagnostic::new_error(err.to_string())])?;
+        let ty = pikelet_core::nbe::nf_term(&self.context, &interface.ty)
+            .map_err(|err| vec![TypeError::from(err).to_diagnostic()])?;
+        // Interfaces without a body can still be type checked against, but
+        // won't reduce any further when they are normalized
+        let import = match interface.body {
+            Some(body) => Import::Term(body),
+            None => Import::Opaque,
+        };
+        self.context.insert_import(path, import, ty);
+
+        Ok(())
+    }
+
     /// Normalize the contents of a file
     pub fn normalize_file(
         &mut self,
         name: FileName,
         src: String,
     ) -> Result<domain::RcValue, Vec<Diagnostic>> {
-        use pikelet_concrete::elaborate::InternalError;
+        use pikelet_concrete::elaborate::TypeError;
 
         let (term, _) = self.infer_file(name, src)?;
         pikelet_core::nbe::nf_term(&self.context, &term)
-            .map_err(|err| vec![InternalError::from(err).to_diagnostic()])
+            .map_err(|err| vec![TypeError::from(err).to_diagnostic()])
+    }
+
+    /// Run the `IO` action contained in a file, writing any output to
+    /// `writer`, and returning the result of the action
+    pub fn run_file(
+        &mut self,
+        name: FileName,
+        src: String,
+        writer: &mut dyn io::Write,
+    ) -> Result<domain::RcValue, Vec<Diagnostic>> {
+        let (term, ty) = self.infer_file(name, src)?;
+        if self.context.io(&ty).is_none() {
+            let found = self.resugar(&ty);
+            let message = format!("expected an `IO` action, but found `{}`", found);
+            return Err(vec![Diagnostic::new_error(message)]);
+        }
+
+        let action = self.normalize_term(&term)?;
+        pikelet_core::run::run_io(&self.context, &action, writer)
+            .map_err(|err| vec![Diagnostic::new_error(err.to_string())])
     }
 
     /// Infer the type of a term
@@ -245,10 +472,18 @@ impl Driver {
 
     /// Normalize a term
     pub fn normalize_term(&self, term: &core::RcTerm) -> Result<domain::RcValue, Vec<Diagnostic>> {
-        use pikelet_concrete::elaborate::InternalError;
+        use pikelet_concrete::elaborate::TypeError;
 
         pikelet_core::nbe::nf_term(&self.context, term)
-            .map_err(|err| vec![InternalError::from(err).to_diagnostic()])
+            .map_err(|err| vec![TypeError::from(err).to_diagnostic()])
+    }
+
+    /// Check a term for lint warnings
+    pub fn lint(&self, term: &concrete::Term) -> Vec<Diagnostic> {
+        pikelet_concrete::lint::check_term(term)
+            .iter()
+            .map(|warning| warning.to_diagnostic())
+            .collect()
     }
 
     /// Desugar a term
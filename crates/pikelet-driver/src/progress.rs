@@ -0,0 +1,51 @@
+//! Reporting the progress of long-running operations
+//!
+//! Checking a large module can take a while, so the driver reports each
+//! stage that it moves through to a `Progress`. Front-ends can use this to
+//! show a progress bar, rather than appearing to have frozen.
+
+/// Something that is told about the progress of an operation
+pub trait Progress {
+    /// Report that the operation has begun the given stage, with `fraction`
+    /// of the operation, between `0.0` and `1.0`, already completed
+    fn report(&mut self, stage: &str, fraction: f32);
+}
+
+/// Ignores all progress reports
+impl Progress for () {
+    fn report(&mut self, _: &str, _: f32) {}
+}
+
+impl<F: FnMut(&str, f32)> Progress for F {
+    fn report(&mut self, stage: &str, fraction: f32) {
+        self(stage, fraction)
+    }
+}
+
+/// Reports the progress of one step in a larger operation, scaling its
+/// fractions to the portion of the operation that the step makes up
+pub struct Step<'a> {
+    progress: &'a mut dyn Progress,
+    index: usize,
+    count: usize,
+}
+
+impl<'a> Step<'a> {
+    /// Report the progress of the step at `index`, out of `count` equally
+    /// sized steps
+    pub fn new(progress: &'a mut dyn Progress, index: usize, count: usize) -> Step<'a> {
+        Step {
+            progress,
+            index,
+            count,
+        }
+    }
+}
+
+impl<'a> Progress for Step<'a> {
+    fn report(&mut self, stage: &str, fraction: f32) {
+        let count = self.count.max(1) as f32;
+        let fraction = (self.index as f32 + fraction) / count;
+        self.progress.report(stage, fraction);
+    }
+}
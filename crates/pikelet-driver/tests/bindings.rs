@@ -0,0 +1,21 @@
+use pikelet_driver::{Driver, FileName};
+
+#[test]
+fn add_binding() {
+    let mut driver = Driver::with_prelude();
+    assert!(driver.bindings().iter().all(|(name, _)| name != "test"));
+
+    let (term, ty) = driver
+        .infer_file(FileName::virtual_("test"), r#""hello""#.to_owned())
+        .unwrap();
+    driver.add_binding("test", term, ty);
+
+    let bindings = driver.bindings();
+    let (_, ty) = bindings.iter().find(|(name, _)| name == "test").unwrap();
+    assert_eq!(ty.to_string(), "String");
+
+    let names = bindings.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let mut sorted_names = names.clone();
+    sorted_names.sort();
+    assert_eq!(names, sorted_names);
+}
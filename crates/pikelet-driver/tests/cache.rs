@@ -0,0 +1,45 @@
+use pikelet_driver::cache::{Cache, Digest};
+use pikelet_driver::Limits;
+
+#[test]
+fn digest_changes_with_inputs() {
+    let limits = Limits::default();
+    let dependency = Digest::new("record {}", limits, &[]);
+    let digest = Digest::new(r#""hello""#, limits, &[dependency]);
+
+    assert_eq!(digest, Digest::new(r#""hello""#, limits, &[dependency]));
+    assert_ne!(digest, Digest::new(r#""hello!""#, limits, &[dependency]));
+    assert_ne!(digest, Digest::new(r#""hello""#, limits, &[]));
+    assert_ne!(
+        digest,
+        Digest::new(
+            r#""hello""#,
+            limits,
+            &[Digest::new("record { }", limits, &[])]
+        ),
+    );
+    assert_ne!(
+        digest,
+        Digest::new(
+            r#""hello""#,
+            Limits {
+                max_depth: 1,
+                ..limits
+            },
+            &[dependency],
+        ),
+    );
+}
+
+#[test]
+fn store_and_load() {
+    let dir = std::env::temp_dir().join(format!("pikelet-cache-test-{}", std::process::id()));
+    let cache = Cache::new(&dir);
+    let digest = Digest::new(r#""hello""#, Limits::default(), &[]);
+
+    assert_eq!(cache.load(&digest), None);
+    cache.store(&digest, b"interface").unwrap();
+    assert_eq!(cache.load(&digest), Some(b"interface".to_vec()));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,113 @@
+use pikelet_driver::interface;
+use pikelet_driver::termcolor::{ColorChoice, StandardStream};
+use pikelet_driver::{Driver, FileName, Interface, Limits};
+
+#[test]
+fn prelude_round_trip() {
+    let mut driver = Driver::with_prelude();
+    let writer = StandardStream::stdout(ColorChoice::Always);
+
+    let (interface, diagnostics) = driver.check_file_interface(
+        FileName::virtual_("prelude"),
+        pikelet_library::PRELUDE.to_owned(),
+        true,
+    );
+    let bytes = match interface {
+        Some(interface) => interface.encode(),
+        None => {
+            driver.emit(writer.lock(), &diagnostics).unwrap();
+            panic!("check error!")
+        },
+    };
+
+    let mut driver = Driver::with_prelude();
+    if let Err(diagnostics) = driver.register_interface("prelude-interface".to_owned(), &bytes) {
+        driver.emit(writer.lock(), &diagnostics).unwrap();
+        panic!("load error!")
+    }
+
+    let src = r#"(import "prelude-interface").id String "hello""#;
+    if let Err(diagnostics) = driver.normalize_file(FileName::virtual_("test"), src.to_owned()) {
+        driver.emit(writer.lock(), &diagnostics).unwrap();
+        panic!("normalize error!")
+    }
+}
+
+#[test]
+fn signature_only() {
+    let mut driver = Driver::with_prelude();
+
+    let (interface, _) =
+        driver.check_file_interface(FileName::virtual_("test"), r#""hello""#.to_owned(), false);
+    let bytes = interface.unwrap().encode();
+
+    let mut driver = Driver::with_prelude();
+    driver
+        .register_interface("test".to_owned(), &bytes)
+        .unwrap();
+
+    let src = r#"import "test" : String"#;
+    let (_, ty) = driver
+        .infer_file(FileName::virtual_("check"), src.to_owned())
+        .unwrap();
+    assert_eq!(driver.resugar(&ty).to_string(), "String");
+
+    let src = r#"import "test""#;
+    let value = driver
+        .normalize_file(FileName::virtual_("normalize"), src.to_owned())
+        .unwrap();
+    assert_eq!(driver.resugar(&value).to_string(), r#"import "test""#);
+}
+
+#[test]
+fn already_registered() {
+    let mut driver = Driver::with_prelude();
+
+    let (interface, _) =
+        driver.check_file_interface(FileName::virtual_("test"), r#""hello""#.to_owned(), true);
+    let bytes = interface.unwrap().encode();
+
+    // Imports that were loaded from source can't be replaced
+    assert!(driver
+        .register_interface("prelude".to_owned(), &bytes)
+        .is_err());
+
+    // Neither can imports that were loaded from an interface
+    driver
+        .register_interface("test".to_owned(), &bytes)
+        .unwrap();
+    assert!(driver
+        .register_interface("test".to_owned(), &bytes)
+        .is_err());
+}
+
+#[test]
+fn bad_magic() {
+    let mut driver = Driver::with_prelude();
+
+    assert!(driver
+        .register_interface("test".to_owned(), b"nope")
+        .is_err());
+    assert!(Interface::decode(b"", Limits::default(), |_| None).is_err());
+}
+
+#[test]
+fn too_deep() {
+    use pikelet_driver::interface::DecodeError;
+
+    // A long run of annotation tags, as might be found in a corrupt file
+    let mut bytes = interface::MAGIC.to_vec();
+    bytes.push(interface::VERSION as u8);
+    bytes.resize(bytes.len() + 1_000_000, 0);
+
+    let limits = Limits::default();
+    match Interface::decode(&bytes, limits, |_| None) {
+        Err(DecodeError::TooLarge(_)) => {},
+        result => panic!("expected a depth error, found: {:?}", result),
+    }
+
+    let mut driver = Driver::with_prelude();
+    assert!(driver
+        .register_interface("test".to_owned(), &bytes)
+        .is_err());
+}
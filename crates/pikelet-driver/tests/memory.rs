@@ -0,0 +1,22 @@
+use pikelet_driver::{Driver, FileName};
+
+#[test]
+fn add_binding() {
+    let mut driver = Driver::with_prelude();
+    let before = driver.memory_usage();
+
+    let src = r#"record { x = "hello" }"#;
+    let (term, ty) = driver
+        .infer_file(FileName::virtual_("test"), src.to_owned())
+        .unwrap();
+    driver.add_binding("test", term.clone(), ty);
+    let after = driver.memory_usage();
+
+    assert_eq!(after.context.definitions, before.context.definitions + 1);
+    assert_eq!(
+        after.context.term_nodes,
+        before.context.term_nodes + term.node_count(),
+    );
+    assert_eq!(after.source_files, before.source_files + 1);
+    assert_eq!(after.source_bytes, before.source_bytes + src.len());
+}
@@ -0,0 +1,51 @@
+use pikelet_driver::progress::{Progress, Step};
+use pikelet_driver::{Driver, FileName};
+
+fn check(src: &str) -> Vec<(String, f32)> {
+    let mut driver = Driver::with_prelude();
+    let mut reports = Vec::new();
+    let mut progress = |stage: &str, fraction: f32| reports.push((stage.to_owned(), fraction));
+
+    let name = FileName::virtual_("test");
+    driver.check_file_interface_with_progress(name, src.to_owned(), false, &mut progress);
+
+    reports
+}
+
+#[test]
+fn check_stages() {
+    let reports = check(r#"record { greeting = "hello" }"#);
+    let stages = reports
+        .iter()
+        .map(|(stage, _)| stage.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        stages,
+        ["parsing", "linting", "desugaring", "elaborating", "done"],
+    );
+    assert!(reports.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    assert_eq!(reports.last().unwrap().1, 1.0);
+}
+
+#[test]
+fn check_stages_parse_error() {
+    let stages = check("record {")
+        .into_iter()
+        .map(|(stage, _)| stage)
+        .collect::<Vec<_>>();
+
+    assert_eq!(stages, ["parsing", "done"]);
+}
+
+#[test]
+fn step() {
+    let mut reports = Vec::new();
+    {
+        let mut progress = |_: &str, fraction: f32| reports.push(fraction);
+        Step::new(&mut progress, 0, 4).report("checking", 0.5);
+        Step::new(&mut progress, 3, 4).report("checking", 1.0);
+    }
+
+    assert_eq!(reports, [0.125, 1.0]);
+}
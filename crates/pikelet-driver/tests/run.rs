@@ -0,0 +1,41 @@
+use pikelet_driver::termcolor::{ColorChoice, StandardStream};
+use pikelet_driver::{Driver, FileName};
+
+#[test]
+fn print_in_sequence() {
+    let mut driver = Driver::with_prelude();
+    let writer = StandardStream::stdout(ColorChoice::Always);
+
+    let src = r#"
+        let
+            prelude = import "prelude";
+            Unit = prelude.Unit;
+        in
+            prelude.bind Unit Unit (prelude.print "hello") (\_ =>
+            prelude.bind String Unit (prelude.pure String "world") prelude.print)
+    "#;
+    let mut output = Vec::new();
+    if let Err(diagnostics) =
+        driver.run_file(FileName::virtual_("test"), src.to_owned(), &mut output)
+    {
+        driver.emit(writer.lock(), &diagnostics).unwrap();
+        panic!("run error!")
+    }
+
+    assert_eq!(String::from_utf8(output).unwrap(), "hello\nworld\n");
+}
+
+#[test]
+fn not_an_action() {
+    let mut driver = Driver::with_prelude();
+
+    let mut output = Vec::new();
+    let result = driver.run_file(
+        FileName::virtual_("test"),
+        r#""hello""#.to_owned(),
+        &mut output,
+    );
+
+    assert!(result.is_err());
+    assert!(output.is_empty());
+}
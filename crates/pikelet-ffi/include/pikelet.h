@@ -0,0 +1,56 @@
+/*
+ * A C-compatible interface for embedding the Pikelet checker.
+ *
+ * Strings passed in must be NUL-terminated and encoded as UTF-8. Anything
+ * returned is owned by the caller, and should be released with the
+ * corresponding `*_free` function.
+ */
+
+#ifndef PIKELET_H
+#define PIKELET_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The diagnostics produced by `pikelet_check`, each rendered to text */
+typedef struct Diagnostics Diagnostics;
+
+/*
+ * Check some source code, returning the errors and warnings that were
+ * encountered along the way. Returns `NULL` if `src` is `NULL`.
+ */
+Diagnostics *pikelet_check(const char *src);
+
+/* The number of diagnostics */
+size_t pikelet_diagnostics_len(const Diagnostics *diagnostics);
+
+/* Returns `true` if any of the diagnostics are errors */
+bool pikelet_diagnostics_has_errors(const Diagnostics *diagnostics);
+
+/*
+ * The rendered text of the diagnostic at `index`, or `NULL` if `index` is out
+ * of bounds. The string is valid until the diagnostics are freed.
+ */
+const char *pikelet_diagnostics_get(const Diagnostics *diagnostics, size_t index);
+
+/* Release diagnostics that were returned by `pikelet_check` */
+void pikelet_diagnostics_free(Diagnostics *diagnostics);
+
+/*
+ * Normalize some source code, returning the pretty printed result. Returns
+ * `NULL` if `src` is `NULL`, or if the source code could not be evaluated.
+ */
+char *pikelet_eval(const char *src);
+
+/* Release a string that was returned by `pikelet_eval` */
+void pikelet_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PIKELET_H */
@@ -0,0 +1,217 @@
+//! A C-compatible interface for embedding the Pikelet checker
+//!
+//! Every call creates a fresh driver with the prelude loaded, so no state is
+//! shared between calls. Strings that are passed in must be NUL-terminated
+//! and encoded as UTF-8. Anything that is returned is owned by the caller,
+//! and should be released with the corresponding `*_free` function.
+//!
+//! Panics are caught before they reach the caller: `pikelet_check` reports
+//! them as a bug in its diagnostics, and `pikelet_eval` returns `NULL`.
+
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use pikelet_driver::termcolor::NoColor;
+use pikelet_driver::{Diagnostic, Driver, FileName, Severity};
+
+/// The name that source code passed over the FFI is reported under
+const FILE_NAME: &str = "input";
+
+/// The diagnostics produced by `pikelet_check`, each rendered to text
+pub struct Diagnostics {
+    has_errors: bool,
+    messages: Vec<CString>,
+}
+
+impl Diagnostics {
+    fn new(driver: &Driver, diagnostics: &[Diagnostic]) -> Diagnostics {
+        let messages = diagnostics.iter().map(|diagnostic| {
+            let mut buffer = NoColor::new(Vec::new());
+            match driver.emit(&mut buffer, Some(diagnostic)) {
+                Ok(()) => to_c_string(buffer.into_inner()),
+                Err(_) => to_c_string(diagnostic.message.clone().into_bytes()),
+            }
+        });
+
+        Diagnostics {
+            has_errors: diagnostics.iter().any(is_error),
+            messages: messages.collect(),
+        }
+    }
+
+    fn bug(message: String) -> Diagnostics {
+        Diagnostics {
+            has_errors: true,
+            messages: vec![to_c_string(format!("bug: {}", message).into_bytes())],
+        }
+    }
+}
+
+fn is_error(diagnostic: &Diagnostic) -> bool {
+    match diagnostic.severity {
+        Severity::Bug | Severity::Error => true,
+        Severity::Warning | Severity::Note | Severity::Help => false,
+    }
+}
+
+/// Convert some bytes to a C string, dropping any interior NUL bytes
+fn to_c_string(mut bytes: Vec<u8>) -> CString {
+    bytes.retain(|&byte| byte != 0);
+    CString::new(bytes).unwrap()
+}
+
+/// Read the source code passed in by the caller
+unsafe fn read_src(src: *const c_char) -> Result<String, String> {
+    CStr::from_ptr(src)
+        .to_str()
+        .map(str::to_owned)
+        .map_err(|_| "the source code is not valid UTF-8".to_owned())
+}
+
+/// Describe the payload of a caught panic
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.as_str(),
+            None => "unknown panic",
+        },
+    };
+
+    format!("the checker panicked: {}", message)
+}
+
+/// Check some source code, returning the errors and warnings that were
+/// encountered along the way
+///
+/// Returns `NULL` if `src` is `NULL`. The result should be released with
+/// `pikelet_diagnostics_free`.
+///
+/// # Safety
+///
+/// `src` must either be `NULL`, or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_check(src: *const c_char) -> *mut Diagnostics {
+    if src.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let src = read_src(src).map_err(Diagnostics::bug)?;
+        let mut driver = Driver::with_prelude();
+        let diagnostics = driver.check_file(FileName::virtual_(FILE_NAME), src);
+        Ok(Diagnostics::new(&driver, &diagnostics))
+    }));
+
+    let diagnostics = match result {
+        Ok(Ok(diagnostics)) | Ok(Err(diagnostics)) => diagnostics,
+        Err(payload) => Diagnostics::bug(panic_message(&*payload)),
+    };
+
+    Box::into_raw(Box::new(diagnostics))
+}
+
+/// The number of diagnostics
+///
+/// # Safety
+///
+/// `diagnostics` must have been returned by `pikelet_check`, and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_diagnostics_len(diagnostics: *const Diagnostics) -> usize {
+    let diagnostics = &*diagnostics;
+    diagnostics.messages.len()
+}
+
+/// Returns `true` if any of the diagnostics are errors
+///
+/// # Safety
+///
+/// `diagnostics` must have been returned by `pikelet_check`, and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_diagnostics_has_errors(diagnostics: *const Diagnostics) -> bool {
+    let diagnostics = &*diagnostics;
+    diagnostics.has_errors
+}
+
+/// The rendered text of the diagnostic at `index`, or `NULL` if `index` is
+/// out of bounds
+///
+/// The string is borrowed from `diagnostics`, and is valid until they are
+/// freed.
+///
+/// # Safety
+///
+/// `diagnostics` must have been returned by `pikelet_check`, and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_diagnostics_get(
+    diagnostics: *const Diagnostics,
+    index: usize,
+) -> *const c_char {
+    let diagnostics = &*diagnostics;
+    match diagnostics.messages.get(index) {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Release diagnostics that were returned by `pikelet_check`
+///
+/// # Safety
+///
+/// `diagnostics` must either be `NULL`, or have been returned by
+/// `pikelet_check` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_diagnostics_free(diagnostics: *mut Diagnostics) {
+    if !diagnostics.is_null() {
+        drop(Box::from_raw(diagnostics));
+    }
+}
+
+/// Normalize some source code, returning the pretty printed result
+///
+/// Returns `NULL` if `src` is `NULL`, or if the source code could not be
+/// evaluated. Use `pikelet_check` to find out why. The result should be
+/// released with `pikelet_string_free`.
+///
+/// # Safety
+///
+/// `src` must either be `NULL`, or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_eval(src: *const c_char) -> *mut c_char {
+    if src.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let src = read_src(src).ok()?;
+        let mut driver = Driver::with_prelude();
+        let value = driver
+            .normalize_file(FileName::virtual_(FILE_NAME), src)
+            .ok()?;
+        Some(driver.resugar(&value).to_string())
+    }));
+
+    match result {
+        Ok(Some(value)) => to_c_string(value.into_bytes()).into_raw(),
+        Ok(None) | Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a string that was returned by `pikelet_eval`
+///
+/// # Safety
+///
+/// `string` must either be `NULL`, or have been returned by `pikelet_eval`
+/// and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn pikelet_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
@@ -0,0 +1,83 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use pikelet_ffi::*;
+
+fn check(src: &str) -> (bool, Vec<String>) {
+    let src = CString::new(src).unwrap();
+    unsafe {
+        let diagnostics = pikelet_check(src.as_ptr());
+        assert!(!diagnostics.is_null());
+
+        let has_errors = pikelet_diagnostics_has_errors(diagnostics);
+        let messages = (0..pikelet_diagnostics_len(diagnostics))
+            .map(|index| {
+                let message = pikelet_diagnostics_get(diagnostics, index);
+                CStr::from_ptr(message).to_str().unwrap().to_owned()
+            })
+            .collect();
+        let len = pikelet_diagnostics_len(diagnostics);
+        assert!(pikelet_diagnostics_get(diagnostics, len).is_null());
+
+        pikelet_diagnostics_free(diagnostics);
+        (has_errors, messages)
+    }
+}
+
+fn eval(src: &str) -> Option<String> {
+    let src = CString::new(src).unwrap();
+    unsafe {
+        let value = pikelet_eval(src.as_ptr());
+        if value.is_null() {
+            return None;
+        }
+        let string = CStr::from_ptr(value).to_str().unwrap().to_owned();
+        pikelet_string_free(value);
+        Some(string)
+    }
+}
+
+#[test]
+fn check_ok() {
+    assert_eq!(check(r#"record { x = "hello" }"#), (false, Vec::new()));
+}
+
+#[test]
+fn check_error() {
+    let (has_errors, messages) = check(r#""hello" : S32"#);
+
+    assert!(has_errors);
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("error:"));
+}
+
+#[test]
+fn check_invalid_utf8() {
+    let src = CString::new(vec![b'"', 0xff, b'"']).unwrap();
+    unsafe {
+        let diagnostics = pikelet_check(src.as_ptr());
+        assert!(pikelet_diagnostics_has_errors(diagnostics));
+        pikelet_diagnostics_free(diagnostics);
+    }
+}
+
+#[test]
+fn eval_ok() {
+    let value = eval(r#"(\x : String => x) "hello""#);
+    assert_eq!(value, Some(r#""hello""#.to_owned()));
+}
+
+#[test]
+fn eval_error() {
+    assert_eq!(eval(r#""hello" : S32"#), None);
+}
+
+#[test]
+fn null_arguments() {
+    unsafe {
+        assert!(pikelet_check(ptr::null()).is_null());
+        assert!(pikelet_eval(ptr::null()).is_null());
+        pikelet_diagnostics_free(ptr::null_mut());
+        pikelet_string_free(ptr::null_mut());
+    }
+}
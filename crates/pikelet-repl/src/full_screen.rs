@@ -0,0 +1,342 @@
+//! A full-screen terminal front-end for the REPL
+//!
+//! This shares the driver and the command parser with the line based REPL,
+//! but keeps the names that are in scope, and the goals of any holes in the
+//! last command, visible in panes alongside the output.
+
+use failure::Error;
+use std::io;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use tui::backend::{Backend, TermionBackend};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
+use tui::Terminal;
+
+use pikelet_concrete::elaborate::TypeError;
+use pikelet_driver::termcolor::{NoColor, StandardStream};
+use pikelet_driver::{Diagnostic, Driver};
+
+use super::{eval_print, preload_files, ControlFlow, Opts, ReplCommand};
+
+/// The commands that can be completed after a `:`
+const COMMANDS: &[&str] = &[
+    "core", "help", "kind", "let", "memory", "quit", "run", "set", "type", "type!",
+];
+
+/// The characters that separate the words that we complete
+fn is_word_separator(ch: char) -> bool {
+    ch.is_whitespace() || "()[]{};,".contains(ch)
+}
+
+/// Returns `true` if the diagnostic was produced for a hole that could not
+/// be elaborated
+fn is_hole(diagnostic: &Diagnostic) -> bool {
+    match diagnostic.code {
+        Some(ref code) => code == TypeError::HOLE_CODE,
+        None => false,
+    }
+}
+
+/// The longest prefix that is shared between all of the candidates
+fn common_prefix<'a>(candidates: &[&'a str]) -> &'a str {
+    let first = candidates.first().map_or("", |candidate| candidate);
+    let len = candidates.iter().fold(first.len(), |len, candidate| {
+        (first.char_indices())
+            .zip(candidate.chars())
+            .take_while(|&((i, ch0), ch1)| i < len && ch0 == ch1)
+            .map(|((i, ch), _)| i + ch.len_utf8())
+            .last()
+            .unwrap_or(0)
+    });
+    &first[..len]
+}
+
+/// The areas of the screen that the panes are drawn to
+struct Panes {
+    output: Rect,
+    context: Rect,
+    goals: Rect,
+    input: Rect,
+}
+
+impl Panes {
+    fn new(size: Rect) -> Panes {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+            .split(size);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+            .split(rows[0]);
+        let sidebar = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(columns[1]);
+
+        Panes {
+            output: columns[0],
+            context: sidebar[0],
+            goals: sidebar[1],
+            input: rows[1],
+        }
+    }
+}
+
+struct App {
+    driver: Driver,
+    prompt: String,
+    /// The line that is currently being edited
+    input: String,
+    /// The lines that have previously been entered
+    history: Vec<String>,
+    /// Our position in the history, if we are browsing it
+    history_index: Option<usize>,
+    /// The lines shown in the output pane
+    output: Vec<String>,
+    /// The number of lines that the output pane is scrolled back by
+    scroll_back: usize,
+    /// The goals of the holes in the last command
+    goals: Vec<String>,
+    /// A message shown below the input, like a list of possible completions
+    status: String,
+    /// The width to pretty print terms to
+    width: usize,
+}
+
+impl App {
+    fn new(driver: Driver, prompt: String) -> App {
+        App {
+            driver,
+            prompt,
+            input: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            output: vec!["Press tab to complete, and ctrl-d to quit".to_owned()],
+            scroll_back: 0,
+            goals: Vec::new(),
+            status: String::new(),
+            width: 80,
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        self.output.extend(text.lines().map(str::to_owned));
+    }
+
+    fn render_diagnostics<'a>(
+        &self,
+        diagnostics: impl IntoIterator<Item = &'a Diagnostic>,
+    ) -> Vec<String> {
+        let mut buffer = NoColor::new(Vec::new());
+        self.driver.emit(&mut buffer, diagnostics).unwrap();
+        let text = String::from_utf8_lossy(buffer.get_ref());
+        text.lines().map(str::to_owned).collect()
+    }
+
+    /// Print the diagnostics to the output pane, moving any holes over to
+    /// the goals pane
+    fn report(&mut self, diagnostics: &[Diagnostic]) {
+        let (holes, others) = diagnostics.iter().partition::<Vec<_>, _>(|d| is_hole(d));
+        self.goals = self.render_diagnostics(holes);
+        let lines = self.render_diagnostics(others);
+        self.output.extend(lines);
+    }
+
+    /// Evaluate the current line
+    fn submit(&mut self) -> ControlFlow {
+        let line = self.input.clone();
+        self.input.clear();
+        if !line.trim().is_empty() {
+            self.history.push(line.clone());
+        }
+        self.history_index = None;
+        self.scroll_back = 0;
+        self.status.clear();
+        self.goals.clear();
+        self.output.push(format!("{}{}", self.prompt, line));
+
+        let repl_command = match line.parse::<ReplCommand>() {
+            Ok(repl_command) => repl_command,
+            Err(diagnostics) => {
+                self.report(&diagnostics);
+                return ControlFlow::Continue;
+            },
+        };
+
+        let mut out = Vec::new();
+        let result = eval_print(&mut self.driver, repl_command, self.width, &mut out);
+        self.print(&String::from_utf8_lossy(&out));
+        for step in self.driver.take_trace() {
+            self.output.push(step.to_string());
+        }
+
+        match result {
+            Ok(control_flow) => control_flow,
+            Err(diagnostics) => {
+                self.report(&diagnostics);
+                ControlFlow::Continue
+            },
+        }
+    }
+
+    /// Complete the word before the cursor, using the command names or the
+    /// names that are currently in scope
+    fn complete(&mut self) {
+        let bindings = self.driver.bindings();
+        let (start, candidates) = match self.input.find(is_word_separator) {
+            None if self.input.starts_with(':') => (1, COMMANDS.to_vec()),
+            _ => {
+                let start = self.input.rfind(is_word_separator).map_or(0, |i| i + 1);
+                let names = bindings.iter().map(|(name, _)| name.as_str()).collect();
+                (start, names)
+            },
+        };
+
+        let prefix = &self.input[start..];
+        let matches = (candidates.into_iter())
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect::<Vec<_>>();
+
+        match matches.len() {
+            0 => self.status = "no completions".to_owned(),
+            1 => {
+                self.input.truncate(start);
+                self.input.push_str(matches[0]);
+                self.status.clear();
+            },
+            _ => {
+                let common = common_prefix(&matches);
+                self.input.truncate(start);
+                self.input.push_str(common);
+                self.status = matches.join("  ");
+            },
+        }
+    }
+
+    /// Replace the input with the previous line in the history
+    fn history_prev(&mut self) {
+        let index = match self.history_index {
+            None => self.history.len(),
+            Some(index) => index,
+        };
+        if index > 0 {
+            self.history_index = Some(index - 1);
+            self.input = self.history[index - 1].clone();
+        }
+    }
+
+    /// Replace the input with the next line in the history
+    fn history_next(&mut self) {
+        match self.history_index {
+            Some(index) if index + 1 < self.history.len() => {
+                self.history_index = Some(index + 1);
+                self.input = self.history[index + 1].clone();
+            },
+            Some(_) => {
+                self.history_index = None;
+                self.input.clear();
+            },
+            None => {},
+        }
+    }
+
+    fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let panes = Panes::new(terminal.size()?);
+        self.width = panes.output.width.saturating_sub(2) as usize;
+
+        let output_height = panes.output.height.saturating_sub(2) as usize;
+        let max_scroll_back = self.output.len().saturating_sub(output_height);
+        self.scroll_back = self.scroll_back.min(max_scroll_back);
+        let scroll = max_scroll_back - self.scroll_back;
+
+        let lines = |lines: &[String]| -> Vec<Text> {
+            (lines.iter())
+                .map(|line| Text::raw(format!("{}\n", line)))
+                .collect()
+        };
+        let output = lines(&self.output);
+        let context = (self.driver.bindings().into_iter())
+            .map(|(name, ty)| Text::raw(format!("{} : {}\n", name, ty)))
+            .collect::<Vec<_>>();
+        let goals = lines(&self.goals);
+        let input = [
+            Text::raw(format!("{}{}\n", self.prompt, self.input)),
+            Text::raw(self.status.clone()),
+        ];
+
+        terminal.draw(|mut f| {
+            let block = |title| Block::default().borders(Borders::ALL).title(title);
+
+            Paragraph::new(output.iter())
+                .block(block("Output"))
+                .scroll(scroll as u16)
+                .render(&mut f, panes.output);
+            Paragraph::new(context.iter())
+                .block(block("Context"))
+                .wrap(true)
+                .render(&mut f, panes.context);
+            Paragraph::new(goals.iter())
+                .block(block("Goals"))
+                .wrap(true)
+                .render(&mut f, panes.goals);
+            Paragraph::new(input.iter())
+                .block(Block::default().borders(Borders::TOP))
+                .render(&mut f, panes.input);
+        })?;
+
+        let cursor_x =
+            panes.input.x as usize + self.prompt.chars().count() + self.input.chars().count();
+        let cursor_x = cursor_x.min(panes.input.right().saturating_sub(1) as usize);
+        terminal.set_cursor(cursor_x as u16, panes.input.y + 1)?;
+        terminal.show_cursor()
+    }
+}
+
+/// Run the REPL in full-screen mode
+pub fn run(opts: Opts) -> Result<(), Error> {
+    let writer = StandardStream::stderr(opts.color.into());
+    let mut driver = Driver::with_prelude();
+    preload_files(&mut driver, &opts.files, &writer)?;
+
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(stdout);
+    let mut terminal = Terminal::new(TermionBackend::new(stdout))?;
+    terminal.clear()?;
+
+    let mut app = App::new(driver, opts.prompt);
+    let mut keys = io::stdin().keys();
+
+    loop {
+        app.draw(&mut terminal)?;
+
+        let key = match keys.next() {
+            Some(key) => key?,
+            None => break,
+        };
+        match key {
+            Key::Char('\n') => {
+                if let ControlFlow::Break = app.submit() {
+                    break;
+                }
+            },
+            Key::Char('\t') => app.complete(),
+            Key::Char(ch) => app.input.push(ch),
+            Key::Backspace => {
+                app.input.pop();
+            },
+            Key::Up => app.history_prev(),
+            Key::Down => app.history_next(),
+            Key::PageUp => app.scroll_back += 10,
+            Key::PageDown => app.scroll_back = app.scroll_back.saturating_sub(10),
+            Key::Ctrl('c') => app.input.clear(),
+            Key::Ctrl('d') => break,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
@@ -2,12 +2,16 @@
 
 use failure::Error;
 use linefeed::{Interface, ReadResult, Signal};
+use std::io::{self, Write};
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use pikelet_driver::termcolor::StandardStream;
 use pikelet_driver::{ColorArg, Diagnostic, Driver, FileName};
 
+#[cfg(feature = "full-screen")]
+mod full_screen;
+
 /// Options for the `repl` subcommand
 #[derive(Debug, structopt::StructOpt)]
 pub struct Opts {
@@ -40,6 +44,11 @@ pub struct Opts {
     )]
     pub history_file: PathBuf,
 
+    /// Use the full-screen terminal interface
+    #[cfg(feature = "full-screen")]
+    #[structopt(long = "tui")]
+    pub tui: bool,
+
     /// Files to preload into the REPL
     #[structopt(name = "FILE", parse(from_os_str))]
     pub files: Vec<PathBuf>,
@@ -67,7 +76,7 @@ fn print_welcome_banner() {
     }
 }
 
-fn print_help_text() {
+fn print_help_text(out: &mut dyn Write) -> io::Result<()> {
     const HELP_TEXT: &[&str] = &[
         "",
         "Command       Arguments        Purpose",
@@ -75,21 +84,33 @@ fn print_help_text() {
         "<term>                         normalize a term",
         ":? :h :help                    display this help text",
         ":core         <term>           print the core representation of a term",
+        ":k :kind      <term>           infer the universe level of a type",
         ":let          <name> = <term>  add a named term to the REPL context",
+        ":memory                        summarize the memory used by the REPL",
         ":q :quit                       quit the repl",
-        ":t :type      <term>           infer the type of a term",
+        ":r :run       <term>           run an `IO` action",
+        ":set trace    on [<depth>]     trace the steps taken by the type checker",
+        ":set trace    off              stop tracing the type checker",
+        ":t :type      <term>           infer the type of a term, as it was written",
+        ":t! :type!    <term>           infer the fully normalized type of a term",
         "",
     ];
 
     for line in HELP_TEXT {
-        println!("{}", line);
+        writeln!(out, "{}", line)?;
     }
+
+    Ok(())
 }
 
 /// Run the `repl` subcommand with the given options
 pub fn run(opts: Opts) -> Result<(), Error> {
-    use std::fs::File;
-    use std::io::Read;
+    #[cfg(feature = "full-screen")]
+    {
+        if opts.tui {
+            return full_screen::run(opts);
+        }
+    }
 
     let interface = Interface::new("repl")?;
     let writer = StandardStream::stderr(opts.color.into());
@@ -107,21 +128,7 @@ pub fn run(opts: Opts) -> Result<(), Error> {
         print_welcome_banner();
     }
 
-    // preload specified files
-    for path in &opts.files {
-        // FIXME: allow for customization of internal path
-        let internal_path = path.to_str().unwrap().to_owned();
-        let external_path = FileName::Real(path.clone());
-
-        let mut file = File::open(path)?;
-        let mut src = String::new();
-        file.read_to_string(&mut src)?;
-
-        if let Err(diagnostics) = driver.register_file(internal_path, external_path, src) {
-            driver.emit(writer.lock(), &diagnostics).unwrap();
-            return Err(failure::format_err!("encountered an error!"));
-        }
-    }
+    preload_files(&mut driver, &opts.files, &writer)?;
 
     loop {
         match interface.read_line()? {
@@ -138,7 +145,12 @@ pub fn run(opts: Opts) -> Result<(), Error> {
                     },
                 };
 
-                match eval_print(&mut driver, repl_command) {
+                let result = eval_print(&mut driver, repl_command, term_width(), &mut io::stdout());
+                for step in driver.take_trace() {
+                    println!("{}", step);
+                }
+
+                match result {
                     Ok(ControlFlow::Continue) => {},
                     Ok(ControlFlow::Break) => break,
                     Err(diagnostics) => driver.emit(writer.lock(), &diagnostics).unwrap(),
@@ -159,8 +171,46 @@ pub fn run(opts: Opts) -> Result<(), Error> {
     Ok(())
 }
 
-#[derive(Clone)]
-enum ControlFlow {
+/// Load the given source files or interfaces into the driver
+fn preload_files(
+    driver: &mut Driver,
+    paths: &[PathBuf],
+    writer: &StandardStream,
+) -> Result<(), Error> {
+    use std::fs::File;
+    use std::io::Read;
+
+    for path in paths {
+        // FIXME: allow for customization of internal path
+        let internal_path = path.to_str().unwrap().to_owned();
+        let external_path = FileName::Real(path.clone());
+
+        let mut file = File::open(path)?;
+        let result = if path.extension() == Some(pikelet_driver::interface::EXTENSION.as_ref()) {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            driver.register_interface(internal_path, &bytes)
+        } else {
+            let mut src = String::new();
+            file.read_to_string(&mut src)?;
+            driver.register_file(internal_path, external_path, src)
+        };
+
+        if let Err(diagnostics) = result {
+            driver.emit(writer.lock(), &diagnostics).unwrap();
+            return Err(failure::format_err!("encountered an error!"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The depth to trace the type checker to, if none was specified
+const DEFAULT_TRACE_DEPTH: usize = 32;
+
+/// What the REPL should do after evaluating a command
+#[derive(Debug, Clone)]
+pub enum ControlFlow {
     Break,
     Continue,
 }
@@ -188,12 +238,25 @@ pub enum ReplCommand {
     /// :help
     /// ```
     Help,
+    /// Print the universe level of a type
+    ///
+    /// ```text
+    /// :k <term>
+    /// :kind <term>
+    /// ```
+    KindOf(String),
     /// Add a declaration to the REPL environment
     ///
     /// ```text
     /// :let <name> = <term>
     /// ```
     Let(String, String),
+    /// Print a summary of the memory used by the REPL
+    ///
+    /// ```text
+    /// :memory
+    /// ```
+    Memory,
     ///  No command
     NoOp,
     /// Quit the REPL
@@ -203,13 +266,37 @@ pub enum ReplCommand {
     /// :quit
     /// ```
     Quit,
-    /// Print the type of the term
+    /// Enable or disable tracing of the type checker, with an optional limit
+    /// on the depth of the steps that are printed
+    ///
+    /// ```text
+    /// :set trace on
+    /// :set trace on <depth>
+    /// :set trace off
+    /// ```
+    SetTrace(Option<usize>),
+    /// Print the type of the term, as it was written in the term's
+    /// annotations, without normalizing it
     ///
     /// ```text
     /// :t <term>
     /// :type <term>
     /// ```
     TypeOf(String),
+    /// Print the fully normalized type of the term
+    ///
+    /// ```text
+    /// :t! <term>
+    /// :type! <term>
+    /// ```
+    NormalizedTypeOf(String),
+    /// Run an `IO` action
+    ///
+    /// ```text
+    /// :r <term>
+    /// :run <term>
+    /// ```
+    Run(String),
 }
 
 impl FromStr for ReplCommand {
@@ -227,6 +314,10 @@ impl FromStr for ReplCommand {
                 .and(many::<String, _>(alpha_num()))
                 .map(|(hd, tl)| format!("{}{}", hd, tl))
         };
+        let depth = || {
+            // Depths too large to fit in a `usize` are effectively unlimited
+            many1::<String, _>(digit()).map(|digits| digits.parse().unwrap_or(usize::MAX))
+        };
 
         let cmd = choice((
             token(':').with(choice((
@@ -238,6 +329,7 @@ impl FromStr for ReplCommand {
                     ))
                     .map(|_| ReplCommand::Help),
                 ),
+                attempt(string("memory").map(|_| ReplCommand::Memory)),
                 attempt(
                     choice((attempt(string("quit")), attempt(string("q"))))
                         .map(|_| ReplCommand::Quit),
@@ -248,12 +340,42 @@ impl FromStr for ReplCommand {
                         .with(anys1())
                         .map(ReplCommand::Core),
                 ),
+                attempt(
+                    choice((attempt(string("type!")), string("t!")))
+                        .with(spaces1())
+                        .with(anys1())
+                        .map(ReplCommand::NormalizedTypeOf),
+                ),
                 attempt(
                     choice((attempt(string("type")), string("t")))
                         .with(spaces1())
                         .with(anys1())
                         .map(ReplCommand::TypeOf),
                 ),
+                attempt(
+                    choice((attempt(string("run")), string("r")))
+                        .with(spaces1())
+                        .with(anys1())
+                        .map(ReplCommand::Run),
+                ),
+                attempt(
+                    choice((attempt(string("kind")), string("k")))
+                        .with(spaces1())
+                        .with(anys1())
+                        .map(ReplCommand::KindOf),
+                ),
+                attempt(
+                    string("set")
+                        .with(spaces1())
+                        .with(string("trace"))
+                        .with(spaces1())
+                        .with(choice((
+                            attempt(string("on").with(optional(attempt(spaces1().with(depth())))))
+                                .map(|depth| Some(depth.unwrap_or(DEFAULT_TRACE_DEPTH))),
+                            attempt(string("off").map(|_| None)),
+                        )))
+                        .map(ReplCommand::SetTrace),
+                ),
                 attempt(
                     string("let")
                         .with(spaces1())
@@ -280,24 +402,33 @@ impl FromStr for ReplCommand {
     }
 }
 
-fn eval_print(
+fn term_width() -> usize {
+    term_size::dimensions()
+        .map(|(width, _)| width)
+        .unwrap_or(1_000_000)
+}
+
+fn output_error(error: io::Error) -> Vec<Diagnostic> {
+    let message = format!("failed to write output: {}", error);
+    vec![Diagnostic::new_error(message)]
+}
+
+/// Evaluate a REPL command, writing the result to `out`, with terms
+/// pretty printed to fit within `width` columns
+pub fn eval_print(
     driver: &mut Driver,
     repl_command: ReplCommand,
+    width: usize,
+    out: &mut dyn Write,
 ) -> Result<ControlFlow, Vec<Diagnostic>> {
     use codespan::ByteSpan;
 
     use pikelet_concrete::syntax::concrete::Term;
 
-    fn term_width() -> usize {
-        term_size::dimensions()
-            .map(|(width, _)| width)
-            .unwrap_or(1_000_000)
-    }
-
     let file_name = FileName::virtual_("repl");
 
     match repl_command {
-        ReplCommand::Help => print_help_text(),
+        ReplCommand::Help => print_help_text(out).map_err(output_error)?,
 
         ReplCommand::Normalize(term_src) => {
             let (term, inferred) = driver.infer_file(file_name, term_src)?;
@@ -308,7 +439,8 @@ fn eval_print(
                 Box::new(driver.resugar(&inferred)),
             );
 
-            println!("{}", ann_term.to_doc().group().pretty(term_width()));
+            let doc = ann_term.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
         },
         ReplCommand::Core(term_src) => {
             use pikelet_core::syntax::core::{RcTerm, Term};
@@ -316,7 +448,8 @@ fn eval_print(
             let (term, inferred) = driver.infer_file(file_name, term_src)?;
             let ann_term = Term::Ann(term, RcTerm::from(Term::from(&*inferred)));
 
-            println!("{}", ann_term.to_doc().group().pretty(term_width()));
+            let doc = ann_term.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
         },
         ReplCommand::Let(name, term_src) => {
             let (term, inferred) = driver.infer_file(file_name, term_src)?;
@@ -327,18 +460,59 @@ fn eval_print(
                 Box::new(driver.resugar(&inferred)),
             );
 
-            println!("{}", ann_term.to_doc().group().pretty(term_width()));
+            let doc = ann_term.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
 
             return Ok(ControlFlow::Continue);
         },
         ReplCommand::TypeOf(term_src) => {
-            let (_, inferred) = driver.infer_file(file_name, term_src)?;
+            use pikelet_concrete::elaborate::elaborated_type;
+
+            let (term, inferred) = driver.infer_file(file_name, term_src)?;
+            let inferred = driver.resugar(&elaborated_type(&term, &inferred));
+
+            let doc = inferred.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
+        },
+        ReplCommand::NormalizedTypeOf(term_src) => {
+            use pikelet_concrete::elaborate::elaborated_type;
+
+            let (term, inferred) = driver.infer_file(file_name, term_src)?;
+            let inferred = driver.normalize_term(&elaborated_type(&term, &inferred))?;
             let inferred = driver.resugar(&inferred);
 
-            println!("{}", inferred.to_doc().group().pretty(term_width()));
+            let doc = inferred.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
         },
+        ReplCommand::KindOf(term_src) => {
+            use pikelet_core::syntax::domain::{RcValue, Value};
 
+            let (_, level) = driver.infer_file_universe(file_name, term_src)?;
+            let inferred = driver.resugar(&RcValue::from(Value::Universe(level)));
+
+            let doc = inferred.to_doc().group();
+            writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
+        },
+        ReplCommand::Run(term_src) => {
+            use pikelet_core::syntax::domain::Value;
+
+            let value = driver.run_file(file_name, term_src, out)?;
+            // Avoid printing the unit value returned by actions like `print`
+            match *value.inner {
+                Value::RecordIntro(ref fields) if fields.is_empty() => {},
+                _ => {
+                    let value = driver.resugar(&value);
+                    let doc = value.to_doc().group();
+                    writeln!(out, "{}", doc.pretty(width)).map_err(output_error)?;
+                },
+            }
+        },
+
+        ReplCommand::Memory => {
+            writeln!(out, "{}", driver.memory_usage()).map_err(output_error)?;
+        },
         ReplCommand::NoOp => {},
+        ReplCommand::SetTrace(max_depth) => driver.set_trace(max_depth),
         ReplCommand::Quit => return Ok(ControlFlow::Break),
     }
 
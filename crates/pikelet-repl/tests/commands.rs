@@ -0,0 +1,129 @@
+use pikelet_concrete::elaborate::TypeError;
+use pikelet_driver::termcolor::{ColorChoice, StandardStream};
+use pikelet_driver::{Diagnostic, Driver};
+use pikelet_repl::{eval_print, ReplCommand};
+
+fn eval(driver: &mut Driver, src: &str) -> Result<String, Vec<Diagnostic>> {
+    let repl_command = src.parse::<ReplCommand>()?;
+    let mut output = Vec::new();
+    eval_print(driver, repl_command, 80, &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn eval_ok(driver: &mut Driver, src: &str) -> String {
+    match eval(driver, src) {
+        Ok(output) => output,
+        Err(diagnostics) => {
+            let writer = StandardStream::stdout(ColorChoice::Always);
+            driver.emit(writer.lock(), &diagnostics).unwrap();
+            panic!("eval error!")
+        },
+    }
+}
+
+#[test]
+fn type_of_ann() {
+    let mut driver = Driver::with_prelude();
+
+    eval_ok(&mut driver, ":let Foo = String");
+    assert_eq!(eval_ok(&mut driver, r#":t "hello" : Foo"#), "Foo\n");
+    assert_eq!(eval_ok(&mut driver, r#":type "hello" : Foo"#), "Foo\n");
+    assert_eq!(eval_ok(&mut driver, r#":t! "hello" : Foo"#), "String\n");
+    assert_eq!(eval_ok(&mut driver, r#":type! "hello" : Foo"#), "String\n");
+}
+
+#[test]
+fn type_of_let() {
+    let mut driver = Driver::with_prelude();
+
+    let src = r#"let Foo = String; in ("hello" : Foo)"#;
+    assert_eq!(
+        eval_ok(&mut driver, &format!(":t {}", src)),
+        "let Foo : Type;\nFoo = String; in Foo\n",
+    );
+    assert_eq!(eval_ok(&mut driver, &format!(":t! {}", src)), "String\n");
+
+    let src = r#"let Foo = String; in ("hello" : String)"#;
+    assert_eq!(eval_ok(&mut driver, &format!(":t {}", src)), "String\n");
+}
+
+#[test]
+fn type_of_fun_intro() {
+    let mut driver = Driver::with_prelude();
+
+    eval_ok(&mut driver, ":let Foo = String");
+    let src = r#"\x : Foo => ("hello" : Foo)"#;
+    assert_eq!(
+        eval_ok(&mut driver, &format!(":t {}", src)),
+        "(x : Foo) -> Foo\n",
+    );
+    assert_eq!(
+        eval_ok(&mut driver, &format!(":t! {}", src)),
+        "(x : String) -> String\n",
+    );
+}
+
+#[test]
+fn type_of_var() {
+    let mut driver = Driver::with_prelude();
+
+    eval_ok(&mut driver, ":let Foo = String");
+    eval_ok(&mut driver, r#":let foo = "hello" : Foo"#);
+    assert_eq!(eval_ok(&mut driver, ":t foo"), "String\n");
+    assert_eq!(eval_ok(&mut driver, ":t! foo"), "String\n");
+}
+
+#[test]
+fn kind_of_type() {
+    let mut driver = Driver::with_prelude();
+
+    assert_eq!(eval_ok(&mut driver, ":k String"), "Type\n");
+    assert_eq!(eval_ok(&mut driver, ":kind Type"), "Type^1\n");
+    assert_eq!(eval_ok(&mut driver, ":k String -> String"), "Type\n");
+}
+
+#[test]
+fn kind_of_term() {
+    let mut driver = Driver::with_prelude();
+
+    match eval(&mut driver, r#":k "hello""#) {
+        Ok(output) => panic!("expected an error, found: {:?}", output),
+        Err(diagnostics) => {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].labels.len(), 1);
+        },
+    }
+}
+
+#[test]
+fn hole_code() {
+    let mut driver = Driver::with_prelude();
+
+    match eval(&mut driver, "? : String") {
+        Ok(output) => panic!("expected an error, found: {:?}", output),
+        Err(diagnostics) => {
+            assert_eq!(diagnostics.len(), 1);
+            let code = diagnostics[0].code.clone();
+            assert_eq!(code, Some(TypeError::HOLE_CODE.to_owned()));
+        },
+    }
+}
+
+#[test]
+fn prelude_num_instances() {
+    let mut driver = Driver::with_prelude();
+
+    eval_ok(&mut driver, r#":let prelude = import "prelude""#);
+    assert_eq!(
+        eval_ok(&mut driver, "prelude.add U8 prelude.Num-U8 1 2"),
+        "3 : U8\n",
+    );
+    assert_eq!(
+        eval_ok(&mut driver, "prelude.one S64 prelude.Num-S64"),
+        "1 : S64\n",
+    );
+
+    // The monoids that make up the instances are local to their definitions
+    assert!(eval(&mut driver, "prelude.Monoid-U8-Add").is_err());
+    assert!(eval(&mut driver, "prelude.Add").is_err());
+}
@@ -0,0 +1,214 @@
+//! Batch checking of Pikelet source files
+
+use failure::Error;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use pikelet_driver::cache::{self, Cache, Digest};
+use pikelet_driver::progress::{Progress, Step};
+use pikelet_driver::termcolor::StandardStream;
+use pikelet_driver::{interface, ColorArg, Diagnostic, Driver, FileName, Limits, Severity};
+
+/// Options for the `check` subcommand
+#[derive(Debug, structopt::StructOpt)]
+pub struct Opts {
+    /// Configure coloring of output
+    #[structopt(
+        long = "color",
+        parse(try_from_str),
+        default_value = "auto",
+        raw(possible_values = "ColorArg::VARIANTS")
+    )]
+    pub color: ColorArg,
+
+    /// Treat warnings as errors
+    #[structopt(long = "deny-warnings")]
+    pub deny_warnings: bool,
+
+    /// The maximum depth that terms may be nested to
+    #[structopt(long = "max-depth")]
+    pub max_depth: Option<usize>,
+
+    /// The maximum depth of recursion during normalization
+    #[structopt(long = "max-eval-depth")]
+    pub max_eval_depth: Option<usize>,
+
+    /// The maximum number of terms that may be evaluated during normalization
+    #[structopt(long = "max-size")]
+    pub max_size: Option<usize>,
+
+    /// Write the interfaces of successfully checked files alongside them, so
+    /// that they can be loaded without checking them again
+    #[structopt(long = "emit-interfaces")]
+    pub emit_interfaces: bool,
+
+    /// The directory to cache the interfaces of checked files in. By default
+    /// nothing is cached, and every file is checked.
+    #[structopt(long = "cache-dir", parse(from_os_str))]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Check every file, even if it is unchanged since it was last checked,
+    /// ignoring `--cache-dir`
+    #[structopt(long = "no-cache")]
+    pub no_cache: bool,
+
+    /// Show the progress of checking the files on standard error
+    #[structopt(long = "progress")]
+    pub progress: bool,
+
+    /// Files to check
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+}
+
+/// A progress bar that is drawn on a single line of standard error, and
+/// redrawn in place as each file is checked
+struct ProgressLine {
+    enabled: bool,
+    /// The file that is currently being checked
+    path: String,
+    /// The length of the line that was last drawn, so that it can be cleared
+    len: usize,
+}
+
+impl ProgressLine {
+    /// The number of characters in the bar itself
+    const WIDTH: usize = 20;
+
+    fn new(enabled: bool) -> ProgressLine {
+        ProgressLine {
+            enabled,
+            path: String::new(),
+            len: 0,
+        }
+    }
+
+    /// Clear the line, so that other output can be written to the terminal
+    fn clear(&mut self) {
+        if self.len > 0 {
+            eprint!("\r{:width$}\r", "", width = self.len);
+            self.len = 0;
+        }
+    }
+}
+
+impl Progress for ProgressLine {
+    fn report(&mut self, stage: &str, fraction: f32) {
+        if !self.enabled {
+            return;
+        }
+
+        let filled = (fraction * ProgressLine::WIDTH as f32).round() as usize;
+        let filled = filled.min(ProgressLine::WIDTH);
+        let line = format!(
+            "[{}{}] {:>3}% {} {}",
+            "#".repeat(filled),
+            " ".repeat(ProgressLine::WIDTH - filled),
+            (fraction * 100.0).round(),
+            stage,
+            self.path,
+        );
+
+        let padding = self.len.saturating_sub(line.len());
+        eprint!("\r{}{:padding$}", line, "", padding = padding);
+        let _ = io::stderr().flush();
+        self.len = line.len();
+    }
+}
+
+/// Promote warnings to errors if `deny_warnings` is set, returning the number
+/// of errors in the diagnostics
+pub fn count_errors(diagnostics: &mut [Diagnostic], deny_warnings: bool) -> usize {
+    let mut error_count = 0;
+    for diagnostic in diagnostics.iter_mut() {
+        if deny_warnings && diagnostic.severity == Severity::Warning {
+            diagnostic.severity = Severity::Error;
+        }
+        if diagnostic.severity >= Severity::Error {
+            error_count += 1;
+        }
+    }
+    error_count
+}
+
+/// Run the `check` subcommand with the given options
+pub fn run(opts: Opts) -> Result<(), Error> {
+    let writer = StandardStream::stderr(opts.color.into());
+    let mut driver = Driver::with_prelude();
+    let mut error_count = 0;
+
+    let default_limits = Limits::default();
+    let limits = Limits {
+        max_depth: opts.max_depth.unwrap_or(default_limits.max_depth),
+        max_eval_depth: opts.max_eval_depth.unwrap_or(default_limits.max_eval_depth),
+        max_size: opts.max_size.unwrap_or(default_limits.max_size),
+    };
+    driver.set_limits(limits);
+
+    let cache = match opts.cache_dir {
+        Some(ref cache_dir) if !opts.no_cache => Some(Cache::new(cache_dir)),
+        Some(_) | None => None,
+    };
+    let prelude_digest = cache::prelude_digest(limits);
+
+    let mut progress = ProgressLine::new(opts.progress);
+    let file_count = opts.files.len();
+
+    for (index, path) in opts.files.iter().enumerate() {
+        progress.path = path.display().to_string();
+        let mut file = File::open(path)?;
+        let mut src = String::new();
+        file.read_to_string(&mut src)?;
+
+        let digest = Digest::new(&src, limits, &[prelude_digest]);
+        if let Some(bytes) = cache.as_ref().and_then(|cache| cache.load(&digest)) {
+            // This file was checked successfully before, and is unchanged
+            Step::new(&mut progress, index, file_count).report("cached", 1.0);
+            if opts.emit_interfaces {
+                let mut file = File::create(path.with_extension(interface::EXTENSION))?;
+                file.write_all(&bytes)?;
+            }
+            continue;
+        }
+
+        let name = FileName::Real(path.clone());
+        let (interface, mut diagnostics) = driver.check_file_interface_with_progress(
+            name,
+            src,
+            true,
+            &mut Step::new(&mut progress, index, file_count),
+        );
+        let file_error_count = count_errors(&mut diagnostics, opts.deny_warnings);
+
+        if !diagnostics.is_empty() {
+            progress.clear();
+            driver.emit(writer.lock(), &diagnostics)?;
+        }
+        error_count += file_error_count;
+
+        if let (0, Some(interface)) = (file_error_count, interface) {
+            let bytes = interface.encode();
+            if opts.emit_interfaces {
+                let mut file = File::create(path.with_extension(interface::EXTENSION))?;
+                file.write_all(&bytes)?;
+            }
+            // Only cache files without any warnings, so that they are still
+            // reported the next time the file is checked
+            if let (true, Some(cache)) = (diagnostics.is_empty(), &cache) {
+                cache.store(&digest, &bytes)?;
+            }
+        }
+    }
+
+    progress.clear();
+
+    match error_count {
+        0 => Ok(()),
+        1 => Err(failure::format_err!("aborting due to a previous error")),
+        _ => Err(failure::format_err!(
+            "aborting due to {} previous errors",
+            error_count,
+        )),
+    }
+}
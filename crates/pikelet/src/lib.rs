@@ -2,6 +2,8 @@
 
 use failure::Error;
 
+pub mod check;
+
 // TODO: test using https://github.com/killercup/assert_cli
 
 #[derive(Debug, structopt::StructOpt)]
@@ -14,10 +16,15 @@ pub struct Opts {
 
 #[derive(Debug, structopt::StructOpt)]
 pub enum Command {
+    /// Check a batch of source files
+    #[structopt(name = "check")]
+    Check(check::Opts),
     /// A REPL for running expressions
+    #[cfg(feature = "lang")]
     #[structopt(name = "repl")]
     Repl(pikelet_repl::Opts),
     /// Start an instance of the the language server
+    #[cfg(feature = "language-server")]
     #[structopt(name = "language-server")]
     LanguageServer(pikelet_language_server::Opts),
 }
@@ -25,7 +32,10 @@ pub enum Command {
 /// Run `pikelet` with the given options
 pub fn run(opts: Opts) -> Result<(), Error> {
     match opts.command {
+        Command::Check(opts) => check::run(opts),
+        #[cfg(feature = "language-server")]
         Command::LanguageServer(opts) => pikelet_language_server::run(opts),
+        #[cfg(feature = "lang")]
         Command::Repl(opts) => pikelet_repl::run(opts),
     }
 }
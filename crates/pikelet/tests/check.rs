@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+use pikelet::check::{self, Opts};
+use pikelet_driver::{Diagnostic, Severity};
+
+/// A file that type checks, but has an unused parameter
+const UNUSED_PARAM: &str = r#"let f (x : String) (y : String) = x; in f "hello" "world""#;
+
+/// Create an empty directory for a test, with the given file in it
+fn test_dir(name: &str, src: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("pikelet-check-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("test.pi");
+    fs::write(&path, src).unwrap();
+    (dir, path)
+}
+
+/// Parse the options for the `check` subcommand from some arguments
+fn opts(args: &[&str]) -> Opts {
+    Opts::from_iter(Some("check").iter().chain(args))
+}
+
+fn cached_file_count(dir: &Path) -> usize {
+    match fs::read_dir(dir.join("cache")) {
+        Ok(entries) => entries.count(),
+        Err(_) => 0,
+    }
+}
+
+#[test]
+fn count_errors_warning() {
+    let mut diagnostics = vec![Diagnostic::new_warning("oops")];
+
+    assert_eq!(check::count_errors(&mut diagnostics, false), 0);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
+
+#[test]
+fn count_errors_deny_warnings() {
+    let mut diagnostics = vec![Diagnostic::new_warning("oops")];
+
+    assert_eq!(check::count_errors(&mut diagnostics, true), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn check_warning() {
+    let (dir, path) = test_dir("warning", UNUSED_PARAM);
+    let path = path.to_str().unwrap();
+
+    assert!(check::run(opts(&["--color=never", path])).is_ok());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_deny_warnings() {
+    let (dir, path) = test_dir("deny-warnings", UNUSED_PARAM);
+    let path = path.to_str().unwrap();
+
+    let args = ["--color=never", "--deny-warnings", path];
+    assert!(check::run(opts(&args)).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_clean_file_is_cached() {
+    let (dir, path) = test_dir("clean", r#""hello""#);
+    let cache_dir = dir.join("cache");
+
+    let args = [
+        "--color=never",
+        "--cache-dir",
+        cache_dir.to_str().unwrap(),
+        path.to_str().unwrap(),
+    ];
+    assert!(check::run(opts(&args)).is_ok());
+    assert_eq!(cached_file_count(&dir), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_warning_is_not_cached() {
+    let (dir, path) = test_dir("warning-cache", UNUSED_PARAM);
+    let cache_dir = dir.join("cache");
+
+    let args = [
+        "--color=never",
+        "--cache-dir",
+        cache_dir.to_str().unwrap(),
+        path.to_str().unwrap(),
+    ];
+    assert!(check::run(opts(&args)).is_ok());
+    assert_eq!(cached_file_count(&dir), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_no_cache() {
+    let (dir, path) = test_dir("no-cache", r#""hello""#);
+    let cache_dir = dir.join("cache");
+
+    let args = [
+        "--color=never",
+        "--cache-dir",
+        cache_dir.to_str().unwrap(),
+        "--no-cache",
+        path.to_str().unwrap(),
+    ];
+    assert!(check::run(opts(&args)).is_ok());
+    assert_eq!(cached_file_count(&dir), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}